@@ -1,10 +1,9 @@
 use std::collections::BTreeMap;
 
 use udled::{
-    tokenizers::Peek, AsChar, AsSlice, AsStr, Buffer, Error, Input, IntoTokenizer, Location,
-    Reader, Span, TokenizerExt,
+    AsChar, AsSlice, AsStr, Buffer, Diagnostic, Error, Input, IntoTokenizer, Reader, TokenizerExt,
 };
-use udled_tokenizers::{Bool, Float, Integer, Str};
+use udled_tokenizers::{Bool, Float, Integer, Str, Whitespace};
 
 const BRACKET_OPEN: char = '{';
 const BRACKET_CLOSE: char = '}';
@@ -12,15 +11,6 @@ const COMMA: char = ',';
 const BRACE_OPEN: char = '[';
 const BRACE_CLOSE: char = ']';
 
-fn whitespace<'input, B>(reader: &mut Reader<'_, 'input, B>) -> Result<Span, Error>
-where
-    B: Buffer<'input>,
-    B::Item: AsChar,
-    B::Source: AsSlice<'input>,
-{
-    reader.parse(' '.or('\n').many().optional().spanned())
-}
-
 fn array<'input, B>(reader: &mut Reader<'_, 'input, B>) -> Result<Value<'input>, Error>
 where
     B: Buffer<'input>,
@@ -28,29 +18,17 @@ where
     B::Source: AsSlice<'input> + AsStr<'input>,
     <B::Source as AsSlice<'input>>::Slice: AsStr<'input>,
 {
-    let ws = whitespace.into_tokenizer();
-
+    // Whitespace between the tokens is skipped by the skipper set in main
     let output = reader
         .parse((
             BRACE_OPEN,
-            &ws,
             value
                 .into_tokenizer()
-                .punctuated(Peek((&ws, COMMA, &ws)))
+                .punctuated(COMMA)
                 .map_ok(|m| m.into_items().collect::<Vec<_>>()),
-            &ws,
-            BRACE_CLOSE.map_err(|m, buffer: &B| {
-                format!(
-                    "Unexpected char {:?}",
-                    buffer
-                        .get(m)
-                        .map(|m| m.item)
-                        .and_then(|m| m.as_char())
-                        .unwrap_or(' ')
-                )
-            }),
+            BRACE_CLOSE,
         ))
-        .map(|m| m.2)?;
+        .map(|m| m.1)?;
 
     Ok(Value::List(output))
 }
@@ -62,21 +40,14 @@ where
     B::Source: AsSlice<'input> + AsStr<'input>,
     <B::Source as AsSlice<'input>>::Slice: AsStr<'input>,
 {
-    let ws = whitespace.into_tokenizer();
     reader.eat(BRACKET_OPEN)?;
 
-    reader.eat(&ws)?;
-
-    let output = (Str, (&ws, ':', &ws), value.into_tokenizer())
-        .punctuated(Peek((&ws, COMMA, &ws)))
-        .map_ok(|m| {
-            m.into_items()
-                .map(|m| (m.0.value.as_str(), m.2))
-                .collect::<BTreeMap<_, _>>()
-        })
-        .parse(reader)?;
-
-    reader.eat(&ws)?;
+    let output = (Str, ':', value.into_tokenizer())
+        .punctuated(COMMA)
+        .parse(reader)?
+        .into_items()
+        .map(|m| (m.0.value.as_str(), m.2))
+        .collect::<BTreeMap<_, _>>();
 
     reader.eat(BRACKET_CLOSE)?;
 
@@ -96,7 +67,7 @@ where
         reader.parse(object.into_tokenizer())
     } else if reader.is(Str) {
         let str = reader.parse(Str)?;
-        Ok(Value::String(str.value.as_str().into()))
+        Ok(Value::String(str.value.as_str()))
     } else if reader.is(Bool) {
         let bool = reader.parse(Bool)?;
         Ok(Value::Bool(bool.value))
@@ -133,16 +104,12 @@ fn main() -> udled::Result<()> {
 
     let mut input = Input::new(JSON);
 
-    let ret = input.parse(value.into_tokenizer());
+    let ret = input.parse(value.into_tokenizer().skipping(Whitespace));
 
     let array = match ret {
         Ok(ret) => ret,
         Err(err) => {
-            let location = Location::from(JSON, err.position()).unwrap();
-
-            println!("{err}");
-            println!("location: {:?}", location);
-
+            println!("{}", Diagnostic::from(&err).render(JSON));
             return Ok(());
         }
     };
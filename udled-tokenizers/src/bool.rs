@@ -44,8 +44,8 @@ mod test {
 
         let (a, _, b) = input.parse((Bool, ' ', Bool)).unwrap();
 
-        assert_eq!(a.value, true);
-        assert_eq!(b.value, false);
+        assert!(a.value);
+        assert!(!b.value);
         assert!(input.is(EOF))
     }
 }
@@ -56,7 +56,8 @@ where
                 Exclude::new('\n'.or(EOF)).many().optional().spanned(),
                 '\n'.optional(),
             )
-                .slice(),
+                .slice()
+                .no_skip(),
         )?;
 
         Ok(item)
@@ -80,8 +81,11 @@ where
 {
     type Token = Item<<B::Source as AsSlice<'input>>::Slice>;
     fn to_token<'a>(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
-        let item =
-            reader.parse((&self.0, Exclude::new(&self.1).many().optional(), &self.1).slice())?;
+        let item = reader.parse(
+            (&self.0, Exclude::new(&self.1).many().optional(), &self.1)
+                .slice()
+                .no_skip(),
+        )?;
 
         Ok(item)
     }
@@ -106,25 +110,27 @@ where
     fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
         let start = reader.position();
 
-        reader.eat(&self.0)?;
+        reader.no_skip(|reader| {
+            reader.eat(&self.0)?;
 
-        let mut depth = 1;
+            let mut depth = 1;
 
-        loop {
-            if reader.is(EOF) {
-                return Err(reader.error("unexpected end of input inside multi-line comment"));
-            } else if reader.eat(&self.0).is_ok() {
-                depth += 1;
-            } else if reader.eat(&self.1).is_ok() {
-                depth -= 1;
+            loop {
+                if reader.is(EOF) {
+                    return Err(reader.error("unexpected end of input inside multi-line comment"));
+                } else if reader.eat(&self.0).is_ok() {
+                    depth += 1;
+                } else if reader.eat(&self.1).is_ok() {
+                    depth -= 1;
 
-                if depth == 0 {
-                    break;
+                    if depth == 0 {
+                        break Ok(());
+                    }
+                } else {
+                    reader.read()?;
                 }
-            } else {
-                reader.read()?;
             }
-        }
+        })?;
 
         let span = Span::new(start, reader.position());
 
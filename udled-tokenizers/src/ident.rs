@@ -17,8 +17,11 @@ where
     type Token = Item<<B::Source as AsSlice<'input>>::Slice>;
 
     fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
-        let item =
-            reader.parse((Alphabetic.or('_'), AlphaNumeric.or('_').many().optional()).slice())?;
+        let item = reader.parse(
+            (Alphabetic.or('_'), AlphaNumeric.or('_').many().optional())
+                .slice()
+                .no_skip(),
+        )?;
 
         Ok(item)
     }
@@ -64,7 +67,7 @@ where
 
         let all = any!(&start_tokenizer, rest_tokenizer);
 
-        reader.parse((&start_tokenizer, all.many()).slice())
+        reader.parse((&start_tokenizer, all.many()).slice().no_skip())
     }
 
     fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
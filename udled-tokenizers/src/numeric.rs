@@ -15,27 +15,29 @@ where
     type Token = Item<i128>;
 
     fn to_token<'a>(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
-        let start = reader.position();
-        let mut val: i128 = 0;
-        let base = 10;
+        reader.no_skip(|reader| {
+            let start = reader.position();
+            let mut val: i128 = 0;
+            let base = 10;
 
-        let sign = if reader.eat('-').is_ok() { -1 } else { 1 };
+            let sign = if reader.eat('-').is_ok() { -1 } else { 1 };
 
-        loop {
-            let ch = reader.parse(Digit(base))?;
+            loop {
+                let ch = reader.parse(Digit(base))?;
 
-            val = (base as i128) * val + (ch.value as i128);
+                val = (base as i128) * val + (ch.value as i128);
 
-            if !reader.is(Digit(base)) {
-                break;
+                if !reader.is(Digit(base)) {
+                    break;
+                }
             }
-        }
 
-        return Ok(Item::new(Span::new(start, reader.position()), val * sign));
+            Ok(Item::new(Span::new(start, reader.position()), val * sign))
+        })
     }
 
     fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
-        reader.is(Peek((opt('-'), Digit(10))))
+        reader.is(Peek((opt('-'), Digit(10))).no_skip())
     }
 }
 
@@ -58,7 +60,8 @@ where
                 Digit(10).many(),
                 ('e'.or('E'), opt('-'), Digit(10).many()).optional(),
             )
-                .spanned(),
+                .spanned()
+                .no_skip(),
         )?;
 
         let string = reader.buffer().source().as_bytes();
@@ -73,7 +76,7 @@ where
     }
 
     fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
-        reader.is(Peek((Integer, '.')))
+        reader.is(Peek((Integer, '.')).no_skip())
     }
 }
 
@@ -1,4 +1,3 @@
-use alloc::format;
 use udled::{
     any, tokenizers::Exclude, AsChar, AsSlice, Buffer, Error, Item, Reader, Tokenizer, TokenizerExt,
 };
@@ -21,9 +20,10 @@ where
                     .or(Exclude::new('\\'.or('"')))
                     .until('"')
                     .optional(),
-                '"'.map_err(|_, _| format!("Expected unicode string")),
+                '"'.map_err(|_, _| "Expected unicode string"),
             )
-                .slice(),
+                .slice()
+                .no_skip(),
         )
     }
 
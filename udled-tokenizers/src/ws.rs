@@ -1,4 +1,4 @@
-use udled::{AsChar, AsSlice, AsStr, Buffer, Char, Item, Tokenizer, TokenizerExt};
+use udled::{AsChar, AsSlice, AsStr, Buffer, Char, Expected, Item, Tokenizer, TokenizerExt};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Whitespace;
@@ -14,12 +14,10 @@ where
         &self,
         reader: &mut udled::Reader<'_, 'input, T>,
     ) -> Result<Self::Token, udled::Error> {
-        let char = reader.parse(Char)?;
-        if char.value.is_whitespace() {
-            Ok(char)
-        } else {
-            Err(reader.error("Whitespace"))
+        if !self.peek(reader) {
+            return Err(reader.expected(Expected::Class("whitespace")));
         }
+        reader.parse(Char)
     }
 
     fn eat(&self, reader: &mut udled::Reader<'_, 'input, T>) -> Result<(), udled::Error> {
@@ -28,11 +26,10 @@ where
     }
 
     fn peek(&self, reader: &mut udled::Reader<'_, 'input, T>) -> bool {
-        let Ok(ret) = reader.parse(Char) else {
-            return false;
-        };
-
-        ret.value.is_whitespace()
+        reader
+            .peek_ch()
+            .and_then(|m| m.as_char())
+            .is_some_and(|m| m.is_whitespace())
     }
 }
 
@@ -50,12 +47,10 @@ where
         &self,
         reader: &mut udled::Reader<'_, 'input, T>,
     ) -> Result<Self::Token, udled::Error> {
-        let char = reader.parse(Char)?;
-        if char.value.is_ascii_whitespace() {
-            Ok(char)
-        } else {
-            Err(reader.error("Whitespace"))
+        if !self.peek(reader) {
+            return Err(reader.expected(Expected::Class("ascii whitespace")));
         }
+        reader.parse(Char)
     }
 
     fn eat(&self, reader: &mut udled::Reader<'_, 'input, T>) -> Result<(), udled::Error> {
@@ -64,11 +59,10 @@ where
     }
 
     fn peek(&self, reader: &mut udled::Reader<'_, 'input, T>) -> bool {
-        let Ok(ret) = reader.parse(Char) else {
-            return false;
-        };
-
-        ret.value.is_ascii_whitespace()
+        reader
+            .peek_ch()
+            .and_then(|m| m.as_char())
+            .is_some_and(|m| m.is_ascii_whitespace())
     }
 }
 
@@ -87,10 +81,10 @@ where
         &self,
         reader: &mut udled::Reader<'_, 'input, T>,
     ) -> Result<Self::Token, udled::Error> {
-        let Ok(char) = reader.parse('\n'.or('\r').or('\u{2028}').or('\u{2029}').slice()) else {
-            return Err(reader.error("Line Feed"));
-        };
-        Ok(char)
+        if !self.peek(reader) {
+            return Err(reader.expected(Expected::Class("line feed")));
+        }
+        reader.parse('\n'.or('\r').or('\u{2028}').or('\u{2029}').slice())
     }
 
     fn eat(&self, reader: &mut udled::Reader<'_, 'input, T>) -> Result<(), udled::Error> {
@@ -99,7 +93,10 @@ where
     }
 
     fn peek(&self, reader: &mut udled::Reader<'_, 'input, T>) -> bool {
-        reader.is('\n'.or('\r').or('\u{2028}').or('\u{2029}'))
+        matches!(
+            reader.peek_ch().and_then(|m| m.as_char()),
+            Some('\n' | '\r' | '\u{2028}' | '\u{2029}')
+        )
     }
 }
 
@@ -1,10 +1,14 @@
+use core::fmt;
+
 use alloc::vec::Vec;
 
+use crate::{Found, Item, Text};
+
 pub struct BufferItem<'a, B>
 where
     B: Buffer<'a>,
 {
-    /// Index in buffer
+    /// Position of the item in the source
     pub index: usize,
 
     pub item: B::Item,
@@ -17,21 +21,73 @@ pub trait Buffer<'a>: Sized {
     type Item;
 
     fn source(&self) -> Self::Source;
-    /// Number of items in the buffer
+    /// The index past the last item.
+    /// For buffers with one index per item, this is the number of items
     fn len(&self) -> usize;
+    /// Returns true if the buffer contains no items
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     /// Get the item at [idx]
     fn get(&self, idx: usize) -> Option<BufferItem<'a, Self>>;
+    /// Describe the item at [idx] for error messages
+    fn describe(&self, idx: usize) -> Option<Found> {
+        let _ = idx;
+        None
+    }
+    /// The index of the item following the item at [idx]
+    fn next_index(&self, idx: usize) -> usize {
+        idx + 1
+    }
+    /// The index of the item before [idx]
+    fn prev_index(&self, idx: usize) -> Option<usize> {
+        idx.checked_sub(1)
+    }
+    /// The index of the item starting at [position] in the source.
+    /// The end position maps to the index past the last item
+    fn index_of(&self, position: usize) -> Option<usize> {
+        let mut idx = 0;
+
+        while let Some(item) = self.get(idx) {
+            match item.index.cmp(&position) {
+                core::cmp::Ordering::Less => idx = self.next_index(idx),
+                core::cmp::Ordering::Equal => return Some(idx),
+                core::cmp::Ordering::Greater => return None,
+            }
+        }
+
+        (position == self.end_position()).then_some(idx)
+    }
+    /// The position in the source at the end of the buffer
+    fn end_position(&self) -> usize {
+        self.prev_index(self.len())
+            .and_then(|idx| self.get(idx))
+            .map(|m| m.index + m.len)
+            .unwrap_or_default()
+    }
 }
 
+/// A buffer over a string, decoding chars as they are read.
+///
+/// Indices are byte offsets into the string, so positions are free
+/// to compute and no per-char index is built up front
+#[derive(Debug, Clone, Copy)]
 pub struct StringBuffer<'a> {
     input: &'a str,
-    chars: Vec<(usize, char)>,
 }
 
 impl<'a> StringBuffer<'a> {
-    pub fn new(input: &'a str) -> StringBuffer<'a> {
-        let chars = input.char_indices().collect();
-        StringBuffer { input, chars }
+    pub const fn new(input: &'a str) -> StringBuffer<'a> {
+        StringBuffer { input }
+    }
+
+    fn decode(&self, idx: usize) -> Option<char> {
+        let byte = *self.input.as_bytes().get(idx)?;
+        if byte.is_ascii() {
+            return Some(byte as char);
+        }
+
+        self.input.get(idx..)?.chars().next()
     }
 }
 
@@ -45,16 +101,50 @@ impl<'a> Buffer<'a> for StringBuffer<'a> {
     }
 
     fn len(&self) -> usize {
-        self.chars.len()
+        self.input.len()
     }
 
     fn get(&self, idx: usize) -> Option<BufferItem<'a, Self>> {
-        self.chars.get(idx).map(|(index, item)| BufferItem {
-            index: *index,
+        self.decode(idx).map(|item| BufferItem {
+            index: idx,
             len: item.len_utf8(),
-            item: *item,
+            item,
         })
     }
+
+    fn describe(&self, idx: usize) -> Option<Found> {
+        self.decode(idx).map(Found::Char)
+    }
+
+    fn next_index(&self, idx: usize) -> usize {
+        if idx >= self.input.len() {
+            return self.input.len();
+        }
+
+        let mut idx = idx + 1;
+        while !self.input.is_char_boundary(idx) {
+            idx += 1;
+        }
+
+        idx
+    }
+
+    fn prev_index(&self, idx: usize) -> Option<usize> {
+        let mut idx = idx.min(self.input.len()).checked_sub(1)?;
+        while !self.input.is_char_boundary(idx) {
+            idx -= 1;
+        }
+
+        Some(idx)
+    }
+
+    fn index_of(&self, position: usize) -> Option<usize> {
+        self.input.is_char_boundary(position).then_some(position)
+    }
+
+    fn end_position(&self) -> usize {
+        self.input.len()
+    }
 }
 
 impl<'a> Buffer<'a> for &'a [u8] {
@@ -77,6 +167,45 @@ impl<'a> Buffer<'a> for &'a [u8] {
             item: *item,
         })
     }
+
+    fn describe(&self, idx: usize) -> Option<Found> {
+        (*self).get(idx).map(|item| Found::Char(*item as char))
+    }
+}
+
+/// A buffer over the tokens of a lexer.
+///
+/// Items are the token kinds, and positions are the spans of the tokens
+/// in the source, so errors point into the source that was lexed
+impl<'a, T> Buffer<'a> for &'a [Item<T>]
+where
+    T: Clone + fmt::Debug,
+{
+    type Source = &'a [Item<T>];
+
+    type Item = T;
+
+    fn source(&self) -> Self::Source {
+        self
+    }
+
+    fn len(&self) -> usize {
+        (*self).len()
+    }
+
+    fn get(&self, idx: usize) -> Option<BufferItem<'a, Self>> {
+        (*self).get(idx).map(|item| BufferItem {
+            index: item.span.start,
+            len: item.span.len(),
+            item: item.value.clone(),
+        })
+    }
+
+    fn describe(&self, idx: usize) -> Option<Found> {
+        (*self)
+            .get(idx)
+            .map(|item| Found::Token(Text::format(format_args!("{:?}", item.value))))
+    }
 }
 
 pub trait IntoBuffer<'a> {
@@ -106,3 +235,82 @@ impl<'a> IntoBuffer<'a> for &'a [u8] {
         self
     }
 }
+
+impl<'a, T> IntoBuffer<'a> for &'a [Item<T>]
+where
+    T: Clone + fmt::Debug,
+{
+    type Buffer = &'a [Item<T>];
+    fn into_buffer(self) -> Self::Buffer {
+        self
+    }
+}
+
+impl<'a, T> IntoBuffer<'a> for &'a Vec<Item<T>>
+where
+    T: Clone + fmt::Debug,
+{
+    type Buffer = &'a [Item<T>];
+    fn into_buffer(self) -> Self::Buffer {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn string_buffer() {
+        let buffer = StringBuffer::new("aæ€😀b");
+
+        let mut idx = 0;
+        let mut items = Vec::new();
+        while let Some(item) = buffer.get(idx) {
+            items.push((item.index, item.item, item.len));
+            idx = buffer.next_index(idx);
+        }
+
+        assert_eq!(
+            items,
+            [
+                (0, 'a', 1),
+                (1, 'æ', 2),
+                (3, '€', 3),
+                (6, '😀', 4),
+                (10, 'b', 1)
+            ]
+        );
+        assert_eq!(buffer.end_position(), 11);
+
+        assert_eq!(buffer.prev_index(10), Some(6));
+        assert_eq!(buffer.prev_index(3), Some(1));
+        assert_eq!(buffer.prev_index(0), None);
+
+        // Not a char boundary
+        assert!(buffer.get(2).is_none());
+        assert_eq!(buffer.next_index(2), 3);
+        assert_eq!(buffer.next_index(7), 10);
+        assert_eq!(buffer.prev_index(8), Some(6));
+
+        // Past the end
+        assert_eq!(buffer.next_index(11), 11);
+        assert_eq!(buffer.next_index(20), 11);
+        assert_eq!(buffer.prev_index(11), Some(10));
+        assert_eq!(buffer.prev_index(20), Some(10));
+    }
+
+    #[test]
+    fn token_buffer() {
+        let tokens = [
+            Item::new(crate::Span::new(0, 3), "let"),
+            Item::new(crate::Span::new(4, 5), "x"),
+        ];
+        let buffer = &tokens[..];
+
+        let item = Buffer::get(&buffer, 1).unwrap();
+        assert_eq!((item.index, item.item, item.len), (4, "x", 1));
+        assert_eq!(buffer.end_position(), 5);
+        assert_eq!(buffer.describe(0), Some(Found::Token("\"let\"".into())));
+    }
+}
@@ -2,23 +2,51 @@ use core::marker::PhantomData;
 
 use crate::{
     buffer::{Buffer, BufferItem},
-    error::Error,
+    error::{Error, Expected, Found},
+    state::State,
+    tokenizers::Skip,
+    Span,
 };
 
 pub(crate) struct Cursor<'a, 'input, B> {
     index: &'a mut usize,
     buffer: &'a B,
+    state: &'a mut State,
+    /// Trivia skipped before each token
+    skip: Option<&'a (dyn Skip<'input, B> + 'a)>,
     life: PhantomData<&'input ()>,
 }
 
 impl<'a, 'input, B> Cursor<'a, 'input, B> {
-    pub fn new(index: &'a mut usize, buffer: &'a B) -> Cursor<'a, 'input, B> {
+    pub fn new(index: &'a mut usize, buffer: &'a B, state: &'a mut State) -> Cursor<'a, 'input, B> {
         Cursor {
             index,
             buffer,
+            state,
+            skip: None,
             life: PhantomData,
         }
     }
+
+    pub fn skip(&self) -> Option<&'a (dyn Skip<'input, B> + 'a)> {
+        self.skip
+    }
+
+    pub fn state(&mut self) -> &mut State {
+        self.state
+    }
+
+    pub fn state_ref(&self) -> &State {
+        self.state
+    }
+
+    pub fn index(&self) -> usize {
+        *self.index
+    }
+
+    pub fn set_index(&mut self, index: usize) {
+        *self.index = index;
+    }
 }
 
 impl<'a, 'input, B> Cursor<'a, 'input, B>
@@ -30,83 +58,134 @@ where
     }
 
     pub fn peekn(&self, n: usize) -> Option<BufferItem<'input, B>> {
-        self.buffer.get(*self.index + n)
+        let mut index = *self.index;
+        for _ in 0..n {
+            index = self.buffer.next_index(index);
+        }
+        self.buffer.get(index)
     }
 
     pub fn eat(&mut self) -> Option<BufferItem<'input, B>> {
         let ch = self.buffer.get(*self.index);
-        *self.index += 1;
+        *self.index = self.buffer.next_index(*self.index);
 
         ch
     }
 
     pub fn position(&self) -> usize {
-        let len = self.buffer.len();
-        if len == 0 {
-            return 0;
-        }
-        self.buffer.get(*self.index).map(|m| m.index).unwrap_or(
-            self.buffer
-                .get(len - 1)
-                .map(|m| m.index + m.len)
-                .unwrap_or_default(),
-        )
+        self.buffer
+            .get(*self.index)
+            .map(|m| m.index)
+            .unwrap_or_else(|| self.buffer.end_position())
     }
 
     pub fn prev_position(&self) -> usize {
-        let len = self.buffer.len();
-        if len == 0 {
-            return 0;
-        }
-
-        let index = *self.index;
-        if index == 0 {
-            return 0;
-        }
-
-        self.buffer.get(index - 1).map(|m| m.index).unwrap_or(
-            self.buffer
-                .get(len - 1)
-                .map(|m| m.index + m.len)
-                .unwrap_or_default(),
-        )
+        self.buffer
+            .prev_index(*self.index)
+            .and_then(|index| self.buffer.get(index))
+            .map(|m| m.index)
+            .unwrap_or_else(|| {
+                if *self.index == 0 {
+                    0
+                } else {
+                    self.buffer.end_position()
+                }
+            })
     }
 
     pub fn child<F, R>(&mut self, func: F) -> Result<R, Error>
     where
         F: FnOnce(Cursor<'_, 'input, B>) -> Result<R, Error>,
     {
+        let skip = self.skip;
+        self.child_with(skip, func)
+    }
+
+    /// Like [Cursor::child], skipping trivia with [skip] instead
+    pub fn child_with<F, R>(
+        &mut self,
+        skip: Option<&(dyn Skip<'input, B> + '_)>,
+        func: F,
+    ) -> Result<R, Error>
+    where
+        F: FnOnce(Cursor<'_, 'input, B>) -> Result<R, Error>,
+    {
+        let position = self.position();
+        self.state.enter(position)?;
+
         let mut next_idx = *self.index;
+        let mark = self.state.diagnostics().len();
 
         let child = Cursor {
             index: &mut next_idx,
             buffer: self.buffer,
+            state: &mut *self.state,
+            skip,
             life: PhantomData,
         };
 
-        match func(child) {
+        let ret = func(child);
+        self.state.leave();
+
+        match ret {
             Ok(ret) => {
                 *self.index = next_idx;
 
                 Ok(ret)
             }
-            Err(err) => Err(err),
+            Err(err) => {
+                self.state.truncate_diagnostics(mark);
+                Err(err)
+            }
         }
     }
 
-    pub fn child_peek<F, R>(&mut self, func: F) -> R
+    /// Run [func] without moving the cursor.
+    /// Returns None if a limit was exceeded
+    pub fn child_peek<F, R>(&mut self, func: F) -> Option<R>
     where
         F: FnOnce(Cursor<'_, 'input, B>) -> R,
     {
+        let position = self.position();
+        self.state.enter(position).ok()?;
+
         let mut next_idx = *self.index;
+        let mark = self.state.diagnostics().len();
 
         let child = Cursor {
             index: &mut next_idx,
             buffer: self.buffer,
+            state: &mut *self.state,
+            skip: self.skip,
             life: PhantomData,
         };
 
-        func(child)
+        let ret = func(child);
+        self.state.leave();
+        self.state.truncate_diagnostics(mark);
+        Some(ret)
+    }
+
+    /// Create an error expecting [expected] at the current position
+    /// and track it as a failure
+    pub fn expected(&mut self, expected: Expected) -> Error {
+        let error = match self.peek() {
+            Some(item) => {
+                let error = Error::expected(Span::new(item.index, item.index + item.len), expected);
+                match self.buffer.describe(*self.index) {
+                    Some(found) => error.with_found(found),
+                    None => error,
+                }
+            }
+            None => {
+                let position = self.position();
+                Error::expected(Span::new(position, position), expected).with_found(Found::Eof)
+            }
+        };
+
+        self.state.fail(&error);
+
+        error
     }
 
     pub fn buffer(&self) -> &B {
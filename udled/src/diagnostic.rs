@@ -0,0 +1,619 @@
+use core::fmt::{self, Write};
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{Error, ErrorKind, LineIndex, SourceMap, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+            Severity::Note => f.write_str("note"),
+            Severity::Help => f.write_str("help"),
+        }
+    }
+}
+
+/// A span in the source, with an optional message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub span: Span,
+    pub message: Option<String>,
+    pub primary: bool,
+}
+
+impl Label {
+    pub fn primary(span: Span) -> Label {
+        Label {
+            span,
+            message: None,
+            primary: true,
+        }
+    }
+
+    pub fn secondary(span: Span) -> Label {
+        Label {
+            span,
+            message: None,
+            primary: false,
+        }
+    }
+
+    pub fn with_message(mut self, message: impl ToString) -> Label {
+        self.message = Some(message.to_string());
+        self
+    }
+}
+
+/// A message about the source, pointing at one or more spans
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl ToString) -> Diagnostic {
+        Diagnostic {
+            severity,
+            message: message.to_string(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl ToString) -> Diagnostic {
+        Diagnostic::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl ToString) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, message)
+    }
+
+    pub fn with_label(mut self, label: Label) -> Diagnostic {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_note(mut self, note: impl ToString) -> Diagnostic {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    /// Transform the spans of all labels, e.g. to move them into the global
+    /// span space of a [SourceMap]
+    pub fn map_spans<F: Fn(Span) -> Span>(mut self, func: F) -> Diagnostic {
+        for label in &mut self.labels {
+            label.span = func(label.span);
+        }
+        self
+    }
+
+    /// Render the diagnostic as plain text
+    pub fn render(&self, source: &str) -> String {
+        Renderer::new(source).render(self)
+    }
+}
+
+impl From<&Error> for Diagnostic {
+    fn from(error: &Error) -> Self {
+        let label = match error.kind() {
+            ErrorKind::Expected {
+                found: Some(found), ..
+            } => Label::primary(error.span()).with_message(alloc::format!("unexpected {found}")),
+            _ => Label::primary(error.span()),
+        };
+
+        let mut message = error.kind().to_string();
+        for (_, frame) in error.context() {
+            message.push_str(", ");
+            message.push_str(frame);
+        }
+
+        let mut diagnostic = Diagnostic::error(message).with_label(label);
+
+        for (span, message) in error.labels().chain(error.context()) {
+            diagnostic = diagnostic.with_label(Label::secondary(span).with_message(message));
+        }
+
+        for error in error.errors() {
+            diagnostic = diagnostic.with_note(error);
+        }
+
+        diagnostic
+    }
+}
+
+impl From<Error> for Diagnostic {
+    fn from(error: Error) -> Self {
+        Diagnostic::from(&error)
+    }
+}
+
+#[cfg_attr(not(feature = "color"), allow(dead_code))]
+#[derive(Debug, Clone, Copy)]
+enum Style {
+    Severity(Severity),
+    Secondary,
+    Gutter,
+    Bold,
+}
+
+#[cfg(feature = "color")]
+impl Style {
+    fn ansi(self) -> &'static str {
+        match self {
+            Style::Severity(Severity::Error) => "\x1b[1;31m",
+            Style::Severity(Severity::Warning) => "\x1b[1;33m",
+            Style::Severity(Severity::Note) => "\x1b[1;36m",
+            Style::Severity(Severity::Help) => "\x1b[1;32m",
+            Style::Secondary | Style::Gutter => "\x1b[1;34m",
+            Style::Bold => "\x1b[1m",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Sources<'a> {
+    Single {
+        source: &'a str,
+        index: LineIndex,
+        name: Option<&'a str>,
+    },
+    Map(&'a SourceMap),
+}
+
+/// The labels of a diagnostic pointing into a single source
+struct Snippet<'a, 'l> {
+    name: Option<&'a str>,
+    source: &'a str,
+    index: &'a LineIndex,
+    labels: Vec<SnippetLabel<'l>>,
+}
+
+struct SnippetLabel<'l> {
+    label: &'l Label,
+    start: usize,
+    end: usize,
+    start_line: usize,
+    end_line: usize,
+}
+
+impl<'a, 'l> Snippet<'a, 'l> {
+    fn push(&mut self, label: &'l Label, span: Span) {
+        let start = self.clamp(span.start);
+        let end = self.clamp(span.end.max(span.start));
+        let start_line = self.line(start);
+        let end_line = self.line(if end > start { end - 1 } else { end });
+
+        self.labels.push(SnippetLabel {
+            label,
+            start,
+            end,
+            start_line,
+            end_line,
+        });
+    }
+
+    fn line(&self, position: usize) -> usize {
+        self.index.line(position).unwrap_or_default()
+    }
+
+    fn line_span(&self, line: usize) -> Span {
+        self.index.line_span(line).unwrap_or_default()
+    }
+
+    fn clamp(&self, mut position: usize) -> usize {
+        position = position.min(self.source.len());
+        while !self.source.is_char_boundary(position) {
+            position -= 1;
+        }
+        position
+    }
+}
+
+/// Renders diagnostics as source snippets with line numbers and underlines.
+/// The line index is built once, so a renderer can be reused for many diagnostics
+#[derive(Debug, Clone)]
+pub struct Renderer<'a> {
+    sources: Sources<'a>,
+    #[cfg(feature = "color")]
+    color: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(source: &'a str) -> Renderer<'a> {
+        Renderer {
+            sources: Sources::Single {
+                source,
+                index: LineIndex::new(source),
+                name: None,
+            },
+            #[cfg(feature = "color")]
+            color: false,
+        }
+    }
+
+    /// Render diagnostics with global spans pointing into the files of [map]
+    pub fn from_map(map: &'a SourceMap) -> Renderer<'a> {
+        Renderer {
+            sources: Sources::Map(map),
+            #[cfg(feature = "color")]
+            color: false,
+        }
+    }
+
+    /// Name of the source, printed in front of the location.
+    /// Files of a source map always use their own name
+    pub fn with_name(mut self, name: &'a str) -> Renderer<'a> {
+        if let Sources::Single { name: current, .. } = &mut self.sources {
+            *current = Some(name);
+        }
+        self
+    }
+
+    /// Use ANSI escape codes to color the output
+    #[cfg(feature = "color")]
+    pub fn with_color(mut self, color: bool) -> Renderer<'a> {
+        self.color = color;
+        self
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut output = String::new();
+        // Writing to a string can not fail
+        let _ = self.write(&mut output, diagnostic);
+        output
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W, diagnostic: &Diagnostic) -> fmt::Result {
+        let severity = Style::Severity(diagnostic.severity);
+
+        self.paint(out, severity, &diagnostic.severity)?;
+        self.paint(out, Style::Bold, &format_args!(": {}", diagnostic.message))?;
+        out.write_char('\n')?;
+
+        let snippets = self.snippets(&diagnostic.labels);
+
+        let last_line = snippets
+            .iter()
+            .flat_map(|m| m.labels.iter())
+            .map(|m| m.end_line)
+            .max()
+            .unwrap_or_default();
+        let width = count_digits(last_line + 1);
+
+        for (idx, snippet) in snippets.iter().enumerate() {
+            if idx > 0 {
+                self.gutter(out, width, None)?;
+                out.write_char('\n')?;
+            }
+            self.write_snippet(out, snippet, severity, width, idx == 0)?;
+        }
+
+        if !diagnostic.notes.is_empty() {
+            self.gutter(out, width, None)?;
+            out.write_char('\n')?;
+        }
+
+        for note in &diagnostic.notes {
+            write!(out, "{:width$} ", "")?;
+            self.paint(out, Style::Gutter, &"=")?;
+            self.paint(out, Style::Bold, &" note")?;
+            writeln!(out, ": {note}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Group labels by source, with the source of the first primary label first
+    fn snippets<'l>(&self, labels: &'l [Label]) -> Vec<Snippet<'_, 'l>> {
+        let mut snippets: Vec<(usize, Snippet<'_, 'l>)> = Vec::new();
+
+        let ordered = labels
+            .iter()
+            .filter(|m| m.primary)
+            .chain(labels.iter().filter(|m| !m.primary));
+
+        // Create the groups in order of importance
+        for label in ordered {
+            let Some((key, snippet)) = self.resolve(label.span) else {
+                continue;
+            };
+
+            if !snippets.iter().any(|m| m.0 == key) {
+                snippets.push((key, snippet));
+            }
+        }
+
+        // Then fill them in source order
+        for label in labels {
+            let Some((key, _)) = self.resolve(label.span) else {
+                continue;
+            };
+
+            if let Some((_, snippet)) = snippets.iter_mut().find(|m| m.0 == key) {
+                snippet.push(label, self.local_span(label.span));
+            }
+        }
+
+        snippets.into_iter().map(|m| m.1).collect()
+    }
+
+    fn resolve<'l>(&self, span: Span) -> Option<(usize, Snippet<'_, 'l>)> {
+        match &self.sources {
+            Sources::Single {
+                source,
+                index,
+                name,
+            } => Some((
+                0,
+                Snippet {
+                    name: *name,
+                    source,
+                    index,
+                    labels: Vec::new(),
+                },
+            )),
+            Sources::Map(map) => {
+                let file = map.lookup(span)?;
+                Some((
+                    file.id().index(),
+                    Snippet {
+                        name: Some(file.name()),
+                        source: file.source(),
+                        index: file.index(),
+                        labels: Vec::new(),
+                    },
+                ))
+            }
+        }
+    }
+
+    fn local_span(&self, span: Span) -> Span {
+        match &self.sources {
+            Sources::Single { .. } => span,
+            Sources::Map(map) => map.lookup(span).map(|m| m.local(span)).unwrap_or(span),
+        }
+    }
+
+    fn write_snippet<W: Write>(
+        &self,
+        out: &mut W,
+        snippet: &Snippet<'_, '_>,
+        severity: Style,
+        width: usize,
+        first: bool,
+    ) -> fmt::Result {
+        let labels = &snippet.labels;
+
+        let main = labels
+            .iter()
+            .find(|m| m.label.primary)
+            .or_else(|| labels.first());
+
+        if let Some(main) = main {
+            let column = snippet.source[snippet.line_span(main.start_line).start..main.start]
+                .chars()
+                .count();
+            write!(out, "{:width$}", "")?;
+            self.paint(out, Style::Gutter, if first { &"--> " } else { &"::: " })?;
+            if let Some(name) = snippet.name {
+                write!(out, "{name}:")?;
+            }
+            writeln!(out, "{}:{}", main.start_line + 1, column + 1)?;
+        }
+
+        if !labels.is_empty() {
+            self.gutter(out, width, None)?;
+            out.write_char('\n')?;
+        }
+
+        let mut shown = labels
+            .iter()
+            .flat_map(|m| m.start_line..=m.end_line)
+            .collect::<Vec<_>>();
+        shown.sort_unstable();
+        shown.dedup();
+
+        let mut prev: Option<usize> = None;
+
+        for line in shown {
+            if let Some(prev) = prev {
+                if line > prev + 1 {
+                    self.paint(out, Style::Gutter, &"...")?;
+                    out.write_char('\n')?;
+                }
+            }
+            prev = Some(line);
+
+            let range = snippet.line_span(line);
+            let text = &snippet.source[range.range()];
+
+            self.gutter(out, width, Some(line + 1))?;
+            writeln!(out, " {text}")?;
+
+            for label in labels {
+                if line < label.start_line || line > label.end_line {
+                    continue;
+                }
+
+                let from = if line == label.start_line {
+                    label.start
+                } else {
+                    range.start
+                };
+
+                let to = if line == label.end_line {
+                    label.end
+                } else {
+                    range.end
+                };
+
+                let indent = &snippet.source[range.start..from];
+                let count = snippet.source[from..to].chars().count().max(1);
+
+                let (style, marker) = if label.label.primary {
+                    (severity, '^')
+                } else {
+                    (Style::Secondary, '-')
+                };
+
+                self.gutter(out, width, None)?;
+                out.write_char(' ')?;
+
+                for ch in indent.chars() {
+                    out.write_char(if ch == '\t' { '\t' } else { ' ' })?;
+                }
+
+                let mut underline = String::new();
+                for _ in 0..count {
+                    underline.push(marker);
+                }
+
+                match &label.label.message {
+                    Some(message) if line == label.end_line => {
+                        self.paint(out, style, &format_args!("{underline} {message}"))?
+                    }
+                    _ => self.paint(out, style, &underline)?,
+                }
+
+                out.write_char('\n')?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn gutter<W: Write>(&self, out: &mut W, width: usize, line: Option<usize>) -> fmt::Result {
+        match line {
+            Some(line) => self.paint(out, Style::Gutter, &format_args!("{line:>width$} |")),
+            None => self.paint(out, Style::Gutter, &format_args!("{:width$} |", "")),
+        }
+    }
+
+    fn paint<W: Write>(&self, out: &mut W, style: Style, text: &dyn fmt::Display) -> fmt::Result {
+        #[cfg(feature = "color")]
+        if self.color {
+            return write!(out, "{}{}\x1b[0m", style.ansi(), text);
+        }
+
+        #[cfg(not(feature = "color"))]
+        let _ = style;
+
+        write!(out, "{text}")
+    }
+}
+
+fn count_digits(mut n: usize) -> usize {
+    let mut count = 1;
+    while n >= 10 {
+        n /= 10;
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Found, Input};
+
+    const SOURCE: &str = "let a = [1, 2};\nlet b = 3;\n";
+
+    #[test]
+    fn render_error() {
+        let error = Error::expected(Span::new(13, 14), ']').with_found(Found::Char('}'));
+
+        assert_eq!(
+            Diagnostic::from(&error).render(SOURCE),
+            "\
+error: expected `]`, found `}`
+ --> 1:14
+  |
+1 | let a = [1, 2};
+  |              ^ unexpected `}`
+"
+        );
+    }
+
+    #[test]
+    fn render_labels_and_notes() {
+        let diagnostic = Diagnostic::error("unclosed delimiter")
+            .with_label(Label::secondary(Span::new(8, 9)).with_message("opened here"))
+            .with_label(Label::primary(Span::new(24, 25)).with_message("expected `]`"))
+            .with_note("arrays are closed with `]`");
+
+        assert_eq!(
+            Renderer::new(SOURCE)
+                .with_name("main.txt")
+                .render(&diagnostic),
+            "\
+error: unclosed delimiter
+ --> main.txt:2:9
+  |
+1 | let a = [1, 2};
+  |         - opened here
+2 | let b = 3;
+  |         ^ expected `]`
+  |
+  = note: arrays are closed with `]`
+"
+        );
+    }
+
+    #[test]
+    fn render_multiline() {
+        let source = "a\nbb\nc\nd\ne";
+        let diagnostic = Diagnostic::error("multi")
+            .with_label(Label::primary(Span::new(2, 6)).with_message("here"))
+            .with_label(Label::secondary(Span::new(9, 10)));
+
+        assert_eq!(
+            diagnostic.render(source),
+            "\
+error: multi
+ --> 2:1
+  |
+2 | bb
+  | ^^
+3 | c
+  | ^ here
+...
+5 | e
+  | -
+"
+        );
+    }
+
+    #[test]
+    fn render_parse_error() {
+        let mut input = Input::new("[1}");
+        let error = input.parse(('[', '1', ']')).unwrap_err();
+
+        assert_eq!(
+            Diagnostic::from(error).render("[1}"),
+            "\
+error: expected `]`, found `}`
+ --> 1:3
+  |
+1 | [1}
+  |   ^ unexpected `}`
+"
+        );
+    }
+}
@@ -1,12 +1,386 @@
-use core::fmt;
+use core::{fmt, hash, ops::Deref};
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{borrow::Cow, boxed::Box, string::String, vec, vec::Vec};
+
+use crate::{Span, WithSpan};
+
+/// Something a tokenizer expected to find
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expected {
+    /// A literal char
+    Char(char),
+    /// A literal string
+    Literal(Text),
+    /// A char in the inclusive range
+    Range(char, char),
+    /// A class of chars, like "digit" or "whitespace"
+    Class(&'static str),
+    /// A named rule
+    Named(Text),
+    /// End of input
+    Eof,
+}
+
+impl From<char> for Expected {
+    fn from(value: char) -> Self {
+        Expected::Char(value)
+    }
+}
+
+impl From<&'static str> for Expected {
+    fn from(value: &'static str) -> Self {
+        Expected::Literal(Text::from(value))
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Char(char) => fmt_char(f, *char),
+            Expected::Literal(lit) => write!(f, "`{lit}`"),
+            Expected::Range(start, end) => {
+                fmt_char(f, *start)?;
+                f.write_str("..=")?;
+                fmt_char(f, *end)
+            }
+            Expected::Class(class) => f.write_str(class),
+            Expected::Named(name) => f.write_str(name),
+            Expected::Eof => f.write_str("end of input"),
+        }
+    }
+}
+
+/// The items a tokenizer expected to find.
+/// A single item is stored inline, so a failing literal does not allocate
+#[derive(Debug, Clone, Default)]
+pub struct ExpectedSet(Repr);
+
+#[derive(Debug, Clone)]
+enum Repr {
+    One(Expected),
+    Many(Vec<Expected>),
+}
+
+impl Default for Repr {
+    fn default() -> Self {
+        Repr::Many(Vec::new())
+    }
+}
+
+impl ExpectedSet {
+    pub const fn new() -> ExpectedSet {
+        ExpectedSet(Repr::Many(Vec::new()))
+    }
+
+    pub fn as_slice(&self) -> &[Expected] {
+        match &self.0 {
+            Repr::One(one) => core::slice::from_ref(one),
+            Repr::Many(list) => list,
+        }
+    }
+
+    /// Add [expected], if it is not in the set already
+    pub fn insert(&mut self, expected: Expected) {
+        if self.contains(&expected) {
+            return;
+        }
+
+        match &mut self.0 {
+            Repr::Many(list) if list.capacity() == 0 => self.0 = Repr::One(expected),
+            Repr::Many(list) => list.push(expected),
+            Repr::One(_) => {
+                let Repr::One(first) = core::mem::take(&mut self.0) else {
+                    unreachable!()
+                };
+                self.0 = Repr::Many(vec![first, expected]);
+            }
+        }
+    }
+
+    /// Remove all items, keeping the allocated capacity
+    pub fn clear(&mut self) {
+        match &mut self.0 {
+            Repr::One(_) => self.0 = Repr::default(),
+            Repr::Many(list) => list.clear(),
+        }
+    }
+
+    /// Add the items of [other] not in the set
+    fn append(&mut self, other: ExpectedSet) {
+        match other.0 {
+            Repr::One(one) => self.insert(one),
+            Repr::Many(list) => list.into_iter().for_each(|next| self.insert(next)),
+        }
+    }
+}
+
+impl Deref for ExpectedSet {
+    type Target = [Expected];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl PartialEq for ExpectedSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for ExpectedSet {}
+
+impl hash::Hash for ExpectedSet {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl FromIterator<Expected> for ExpectedSet {
+    fn from_iter<T: IntoIterator<Item = Expected>>(iter: T) -> Self {
+        let mut set = ExpectedSet::new();
+        iter.into_iter().for_each(|next| set.insert(next));
+        set
+    }
+}
+
+/// The item found where a tokenizer failed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Found {
+    Char(char),
+    /// A token in a token buffer
+    Token(Text),
+    Eof,
+}
+
+impl fmt::Display for Found {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Found::Char(char) => fmt_char(f, *char),
+            Found::Token(token) => f.write_str(token),
+            Found::Eof => f.write_str("end of input"),
+        }
+    }
+}
+
+fn fmt_char(f: &mut fmt::Formatter<'_>, char: char) -> fmt::Result {
+    if char.is_control() || char.is_whitespace() {
+        write!(f, "{char:?}")
+    } else {
+        write!(f, "`{char}`")
+    }
+}
+
+/// Text in an error.
+/// Short strings are stored inline, so describing a failure does not allocate
+#[derive(Clone)]
+pub struct Text(TextRepr);
+
+const INLINE: usize = 22;
+
+#[derive(Clone)]
+enum TextRepr {
+    Static(&'static str),
+    Inline(u8, [u8; INLINE]),
+    Heap(Box<str>),
+}
+
+impl Text {
+    /// Copy [text], inline if it is short
+    pub fn new(text: &str) -> Text {
+        let mut buf = [0; INLINE];
+        match buf.get_mut(..text.len()) {
+            Some(slot) => {
+                slot.copy_from_slice(text.as_bytes());
+                Text(TextRepr::Inline(text.len() as u8, buf))
+            }
+            None => Text(TextRepr::Heap(text.into())),
+        }
+    }
+
+    /// Format [args], inline if the output is short
+    pub fn format(args: fmt::Arguments<'_>) -> Text {
+        if let Some(text) = args.as_str() {
+            return Text(TextRepr::Static(text));
+        }
+
+        let mut writer = InlineWriter {
+            len: 0,
+            buf: [0; INLINE],
+        };
+
+        match fmt::write(&mut writer, args) {
+            Ok(()) => Text(TextRepr::Inline(writer.len as u8, writer.buf)),
+            Err(_) => Text(TextRepr::Heap(alloc::fmt::format(args).into())),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            TextRepr::Static(text) => text,
+            TextRepr::Inline(len, buf) => {
+                // Only whole strings are copied in, so this never falls back
+                core::str::from_utf8(&buf[..*len as usize]).unwrap_or_default()
+            }
+            TextRepr::Heap(text) => text,
+        }
+    }
+}
+
+/// Writes into an inline buffer, failing when it is full
+struct InlineWriter {
+    len: usize,
+    buf: [u8; INLINE],
+}
+
+impl fmt::Write for InlineWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let slot = self
+            .buf
+            .get_mut(self.len..self.len + s.len())
+            .ok_or(fmt::Error)?;
+        slot.copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+impl From<&'static str> for Text {
+    fn from(value: &'static str) -> Self {
+        Text(TextRepr::Static(value))
+    }
+}
+
+impl From<String> for Text {
+    fn from(value: String) -> Self {
+        Text(TextRepr::Heap(value.into_boxed_str()))
+    }
+}
+
+impl From<Cow<'static, str>> for Text {
+    fn from(value: Cow<'static, str>) -> Self {
+        match value {
+            Cow::Borrowed(value) => value.into(),
+            Cow::Owned(value) => value.into(),
+        }
+    }
+}
+
+impl Deref for Text {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Text {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq for Text {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Text {}
+
+impl hash::Hash for Text {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+/// A limit set on [Input](crate::Input)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// Maximum nesting of tokenizers
+    Depth(usize),
+    /// Maximum number of tokenizers run by a parse
+    Steps(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Depth(depth) => write!(f, "nesting depth limit of {depth} exceeded"),
+            Limit::Steps(steps) => write!(f, "step limit of {steps} exceeded"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// One of [expected] was expected, but [found] was found
+    Expected {
+        expected: ExpectedSet,
+        found: Option<Found>,
+    },
+    /// A custom error
+    Message(Box<dyn core::error::Error + Send + Sync>),
+    /// A limit of the input was exceeded
+    Limit(Limit),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Expected { expected, found } => {
+                match expected.as_slice() {
+                    [] => f.write_str("unexpected input")?,
+                    [one] => write!(f, "expected {one}")?,
+                    [first, rest @ ..] => {
+                        write!(f, "expected one of {first}")?;
+                        for next in rest {
+                            write!(f, ", {next}")?;
+                        }
+                    }
+                }
+
+                match found {
+                    Some(found) if expected.len() > 1 => write!(f, " but found {found}"),
+                    Some(found) => write!(f, ", found {found}"),
+                    None => Ok(()),
+                }
+            }
+            ErrorKind::Message(message) => write!(f, "{message}"),
+            ErrorKind::Limit(limit) => write!(f, "{limit}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Annotation {
+    /// Another span related to the error
+    Label,
+    /// A rule the error occurred in
+    Context,
+}
 
 #[derive(Debug)]
 pub struct Error {
-    position: usize,
-    message: Box<dyn core::error::Error + Send + Sync>,
+    span: Span,
+    kind: ErrorKind,
     errors: Vec<Error>,
+    /// Labels and context frames, in the order they were added.
+    /// They are rare, so they are kept in a boxed slice to keep errors small
+    annotations: Box<[(Annotation, Span, Cow<'static, str>)]>,
+    committed: bool,
 }
 
 impl Error {
@@ -15,9 +389,11 @@ impl Error {
         msg: T,
     ) -> Error {
         Error {
-            position,
-            message: msg.into(),
+            span: Span::new(position, position),
+            kind: ErrorKind::Message(msg.into()),
             errors: Vec::new(),
+            annotations: Box::default(),
+            committed: false,
         }
     }
 
@@ -27,41 +403,383 @@ impl Error {
         errors: Vec<Error>,
     ) -> Error {
         Error {
-            position,
-            message: msg.into(),
+            span: Span::new(position, position),
+            kind: ErrorKind::Message(msg.into()),
             errors,
+            annotations: Box::default(),
+            committed: false,
         }
     }
 
+    /// Create an error expecting [expected] at [span]
+    pub fn expected<E: Into<Expected>>(span: Span, expected: E) -> Error {
+        Error::expected_one_of(span, [expected.into()])
+    }
+
+    /// Create an error expecting one of [expected] at [span]
+    pub fn expected_one_of<I>(span: Span, expected: I) -> Error
+    where
+        I: IntoIterator<Item = Expected>,
+    {
+        Error {
+            span,
+            kind: ErrorKind::Expected {
+                expected: expected.into_iter().collect(),
+                found: None,
+            },
+            errors: Vec::new(),
+            annotations: Box::default(),
+            committed: false,
+        }
+    }
+
+    /// Create an error for an exceeded limit. It is committed, so it is not backtracked
+    pub fn limit(position: usize, limit: Limit) -> Error {
+        Error {
+            span: Span::new(position, position),
+            kind: ErrorKind::Limit(limit),
+            errors: Vec::new(),
+            annotations: Box::default(),
+            committed: true,
+        }
+    }
+
+    /// Set the item found at the error position
+    pub fn with_found(mut self, item: Found) -> Error {
+        if let ErrorKind::Expected { found, .. } = &mut self.kind {
+            *found = Some(item);
+        }
+        self
+    }
+
+    /// Set the span of the error, like the span of the token that failed validation
+    pub fn with_span(mut self, span: Span) -> Error {
+        self.span = span;
+        self
+    }
+
     pub fn position(&self) -> usize {
-        self.position
+        self.span.start
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// The set of expected items. Empty for custom errors
+    pub fn expected_items(&self) -> &[Expected] {
+        match &self.kind {
+            ErrorKind::Expected { expected, .. } => expected,
+            _ => &[],
+        }
+    }
+
+    pub fn found(&self) -> Option<&Found> {
+        match &self.kind {
+            ErrorKind::Expected { found, .. } => found.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Point at another span related to the error, like where a bracket was opened
+    pub fn with_label(mut self, span: Span, message: impl Into<Cow<'static, str>>) -> Error {
+        self.annotate((Annotation::Label, span, message.into()));
+        self
+    }
+
+    pub fn labels(&self) -> impl Iterator<Item = (Span, &str)> + '_ {
+        self.annotations(Annotation::Label)
+    }
+
+    /// Add a frame describing a rule the error occurred in, like "in object".
+    /// Frames are added from the innermost rule out
+    pub fn with_context(mut self, span: Span, message: impl Into<Cow<'static, str>>) -> Error {
+        self.annotate((Annotation::Context, span, message.into()));
+        self
+    }
+
+    /// The context frames, innermost first
+    pub fn context(&self) -> impl Iterator<Item = (Span, &str)> + '_ {
+        self.annotations(Annotation::Context)
+    }
+
+    fn annotate(&mut self, annotation: (Annotation, Span, Cow<'static, str>)) {
+        let mut annotations = core::mem::take(&mut self.annotations).into_vec();
+        annotations.push(annotation);
+        self.annotations = annotations.into_boxed_slice();
+    }
+
+    fn annotations(&self, kind: Annotation) -> impl Iterator<Item = (Span, &str)> + '_ {
+        self.annotations
+            .iter()
+            .filter(move |m| m.0 == kind)
+            .map(|m| (m.1, &*m.2))
+    }
+
+    /// Mark the error as committed. A committed error is not backtracked:
+    /// alternatives are not tried, and repetitions and optionals return it
+    pub fn committed(mut self) -> Error {
+        self.committed = true;
+        self
+    }
+
+    pub fn is_committed(&self) -> bool {
+        self.committed
+    }
+
+    pub(crate) fn clone_expected(&self) -> Option<Error> {
+        match &self.kind {
+            ErrorKind::Expected { expected, found } => Some(Error {
+                span: self.span,
+                kind: ErrorKind::Expected {
+                    expected: expected.clone(),
+                    found: found.clone(),
+                },
+                errors: Vec::new(),
+                annotations: self.annotations.clone(),
+                committed: false,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Make this error a copy of [other], if it is an expected-error.
+    /// The allocations of this error are reused
+    pub(crate) fn clone_expected_from(&mut self, other: &Error) {
+        let (
+            ErrorKind::Expected { expected, found },
+            ErrorKind::Expected {
+                expected: other_expected,
+                found: other_found,
+            },
+        ) = (&mut self.kind, &other.kind)
+        else {
+            return;
+        };
+
+        expected.clear();
+        for next in other_expected.iter() {
+            expected.insert(next.clone());
+        }
+
+        *found = other_found.clone();
+        self.span = other.span;
+        self.errors.clear();
+        self.annotations = other.annotations.clone();
+        self.committed = false;
+    }
+
+    /// Clone the error, if it has no custom messages
+    pub(crate) fn try_clone(&self) -> Option<Error> {
+        let mut error = self.clone_expected()?;
+        error.committed = self.committed;
+        error.errors = self
+            .errors
+            .iter()
+            .map(Error::try_clone)
+            .collect::<Option<_>>()?;
+        Some(error)
+    }
+
+    /// Merge two errors, keeping the one that got furthest.
+    /// If both failed at the same position, the expected sets are combined
+    pub fn merge(self, other: Error) -> Error {
+        let committed = self.committed || other.committed;
+
+        let mut error = match self.position().cmp(&other.position()) {
+            core::cmp::Ordering::Greater => self,
+            core::cmp::Ordering::Less => other,
+            core::cmp::Ordering::Equal => self.merge_same(other),
+        };
+
+        error.committed = committed;
+        error
+    }
+
+    /// Merge the errors of two alternatives.
+    /// Expected-failures at the same position are combined by the furthest failure
+    /// the parse tracks, so the first is returned as is, instead of building a new set.
+    /// The combined items are added back when the error leaves the parse
+    pub(crate) fn merge_alternative(self, other: Error) -> Error {
+        let both_expected = matches!(self.kind, ErrorKind::Expected { .. })
+            && matches!(other.kind, ErrorKind::Expected { .. });
+
+        if both_expected && self.position() == other.position() && !other.committed {
+            self
+        } else {
+            self.merge(other)
+        }
+    }
+
+    fn merge_same(mut self, mut other: Error) -> Error {
+        match (&mut self.kind, other.kind) {
+            (
+                ErrorKind::Expected { expected, found },
+                ErrorKind::Expected {
+                    expected: other_expected,
+                    found: other_found,
+                },
+            ) => {
+                expected.append(other_expected);
+
+                if found.is_none() {
+                    *found = other_found;
+                }
+
+                if other.span.end > self.span.end {
+                    self.span.end = other.span.end;
+                }
+
+                self.errors.append(&mut other.errors);
+
+                // Frames of different rules do not combine
+                let has_context = self.context().next().is_some();
+
+                for next in other.annotations.into_vec() {
+                    let keep = match next.0 {
+                        Annotation::Label => !self.annotations.contains(&next),
+                        Annotation::Context => !has_context,
+                    };
+
+                    if keep {
+                        self.annotate(next);
+                    }
+                }
+
+                self
+            }
+            (ErrorKind::Message(_) | ErrorKind::Limit(_), kind) => {
+                other.kind = kind;
+                self.errors.push(other);
+                self
+            }
+            (_, kind) => {
+                other.kind = kind;
+                other.errors.push(self);
+                other
+            }
+        }
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        if self.errors.is_empty() {
-            write!(f, "@{}: {}", self.position, self.message)
-        } else {
-            // write!(f, "@{}: {}, errors: ", self.position, self.message)?;
+        write!(f, "@{}: {}", self.position(), self.kind)?;
+
+        for (_, message) in self.context() {
+            write!(f, ", {message}")?;
+        }
+
+        if !self.errors.is_empty() {
+            f.write_str(", errors: ")?;
 
             for (k, v) in self.errors.iter().enumerate() {
                 if k > 0 {
-                    writeln!(f, ", ")?;
+                    write!(f, ", ")?;
                 }
 
                 write!(f, "{}", v)?;
             }
-
-            Ok(())
         }
+
+        Ok(())
+    }
+}
+
+impl WithSpan for Error {
+    fn span(&self) -> Span {
+        self.span
     }
 }
 
 impl core::error::Error for Error {
-    fn cause(&self) -> Option<&dyn core::error::Error> {
-        Some(&*self.message)
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Message(message) => Some(&**message),
+            _ => None,
+        }
     }
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn text() {
+        assert!(matches!(Text::new("short").0, TextRepr::Inline(..)));
+        assert!(matches!(
+            Text::new("a literal too long to be inline").0,
+            TextRepr::Heap(_)
+        ));
+
+        let text = Text::format(format_args!("{:?}", "token"));
+        assert!(matches!(text.0, TextRepr::Inline(..)));
+        assert_eq!(text, Text::from("\"token\""));
+
+        let text = Text::format(format_args!("{}", "a".repeat(30)));
+        assert_eq!(text.len(), 30);
+    }
+
+    #[test]
+    fn display() {
+        let err = Error::expected(Span::new(2, 3), ']').with_found(Found::Char('}'));
+        assert_eq!(err.to_string(), "@2: expected `]`, found `}`");
+
+        let err = Error::expected(Span::new(2, 2), Expected::Eof).with_found(Found::Char('\n'));
+        assert_eq!(err.to_string(), "@2: expected end of input, found '\\n'");
+    }
+
+    #[test]
+    fn merge_furthest() {
+        let near = Error::expected(Span::new(1, 2), ',');
+        let far = Error::expected(Span::new(4, 5), ']');
+
+        assert_eq!(near.merge(far).position(), 4);
+
+        let near = Error::expected(Span::new(1, 2), ',');
+        let far = Error::expected(Span::new(4, 5), ']');
+
+        assert_eq!(far.merge(near).position(), 4);
+    }
+
+    #[test]
+    fn merge_same_position() {
+        let a = Error::expected(Span::new(4, 5), ']').with_found(Found::Char('}'));
+        let b = Error::expected(Span::new(4, 5), ',');
+        let c = Error::expected(Span::new(4, 5), ']');
+
+        let err = a.merge(b).merge(c);
+
+        assert_eq!(
+            err.expected_items(),
+            &[Expected::Char(']'), Expected::Char(',')]
+        );
+        assert_eq!(
+            err.to_string(),
+            "@4: expected one of `]`, `,` but found `}`"
+        );
+    }
+
+    #[test]
+    fn merge_message() {
+        let a = Error::new(4, "custom");
+        let b = Error::expected(Span::new(4, 5), ',');
+
+        let err = a.merge(b);
+        assert!(matches!(err.kind(), ErrorKind::Message(_)));
+        assert_eq!(err.errors().len(), 1);
+    }
+}
@@ -1,9 +1,13 @@
-use core::marker::PhantomData;
+use core::{marker::PhantomData, ops::RangeBounds};
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, vec};
 
 use crate::{
-    tokenizers::{AsDigits, Many, Opt, Or, Puntuated, Sliced, Spanned, Until},
+    tokenizers::{
+        AsDigits, Context, Cut, Delimited, Labelled, LeftRecursive, Lookahead, Many, Memoize,
+        Named, NoSkip, Opt, Or, Puntuated, Recover, RepeatRange, Sequence, Skipping, Sliced,
+        Spanned, Until,
+    },
     AsSlice, Buffer, Error, Item, Reader, Span, Tokenizer,
 };
 
@@ -23,6 +27,66 @@ where
         }
     }
 
+    /// Convert the token with a fallible [func].
+    /// An error is reported at the span of the token
+    fn try_map<F, U, E>(self, func: F) -> TryMap<Self, F, B>
+    where
+        F: Fn(Self::Token) -> Result<U, E>,
+        E: Into<Box<dyn core::error::Error + Send + Sync>>,
+        Self: Sized,
+    {
+        TryMap {
+            tokenizer: self,
+            func,
+            ph: PhantomData,
+        }
+    }
+
+    /// Fail with [message] at the span of the token, if [pred] does not hold
+    fn verify<F>(self, pred: F, message: &'static str) -> Verify<Self, F, B>
+    where
+        F: Fn(&Self::Token) -> bool,
+        Self: Sized,
+    {
+        Verify {
+            tokenizer: self,
+            pred,
+            message,
+            ph: PhantomData,
+        }
+    }
+
+    /// Continue parsing with [func], which is given the token and the reader
+    fn and_then<F, U>(self, func: F) -> AndThen<Self, F, B>
+    where
+        F: Fn(Self::Token, &mut Reader<'_, 'input, B>) -> Result<U, Error>,
+        Self: Sized,
+    {
+        AndThen {
+            tokenizer: self,
+            func,
+            ph: PhantomData,
+        }
+    }
+
+    /// Name the rule in errors. If it fails without consuming input, it is
+    /// reported as expecting [name], otherwise "while parsing [name]" is added to the error
+    fn label(self, name: &'static str) -> Labelled<Self, B>
+    where
+        Self: Sized,
+    {
+        Labelled::new(self, name)
+    }
+
+    /// Add [message], like "in array element", to errors of the rule
+    fn context(self, message: &'static str) -> Context<Self, B>
+    where
+        Self: Sized,
+    {
+        Context::new(self, message)
+    }
+
+    /// Replace the message of errors. The original error is kept in [Error::errors]
     fn map_err<F, U>(self, func: F) -> MapErr<Self, F, B>
     where
         F: Fn(usize, &B) -> U,
@@ -36,15 +100,47 @@ where
         }
     }
 
-    fn repeat(self, count: i32) -> Repeat<Self, B>
+    /// Match exactly [count] times
+    fn repeat(self, count: usize) -> RepeatRange<Self, B>
     where
         Self: Sized,
     {
-        Repeat {
-            tokenizer: self,
-            count,
-            ph: PhantomData,
-        }
+        RepeatRange::new(self, count..=count)
+    }
+
+    /// Match a number of times in [range], see [RepeatRange]
+    ///
+    /// # Panics
+    ///
+    /// If [range] is empty, like `5..5` or `..0`
+    fn repeat_range<R>(self, range: R) -> RepeatRange<Self, B>
+    where
+        Self: Sized,
+        R: RangeBounds<usize>,
+    {
+        RepeatRange::new(self, range)
+    }
+
+    /// Match zero or more times
+    fn many0(self) -> RepeatRange<Self, B>
+    where
+        Self: Sized,
+    {
+        RepeatRange::new(self, ..)
+    }
+
+    fn at_least(self, count: usize) -> RepeatRange<Self, B>
+    where
+        Self: Sized,
+    {
+        RepeatRange::new(self, count..)
+    }
+
+    fn at_most(self, count: usize) -> RepeatRange<Self, B>
+    where
+        Self: Sized,
+    {
+        RepeatRange::new(self, ..=count)
     }
 
     fn many(self) -> Many<Self, B>
@@ -54,6 +150,22 @@ where
         Many::new(self)
     }
 
+    /// Remember the results of the tokenizer, see [Memoize]
+    fn memoize(self, id: &'static str) -> Memoize<Self, B>
+    where
+        Self: Sized,
+    {
+        Memoize::new(self, id)
+    }
+
+    /// Mark a reference to a left recursive rule, see [LeftRecursive]
+    fn left_recursive(self, id: &'static str) -> LeftRecursive<Self, B>
+    where
+        Self: Sized,
+    {
+        LeftRecursive::new(self, id)
+    }
+
     fn until<U>(self, until: U) -> Until<Self, U, B>
     where
         Self: Sized,
@@ -62,6 +174,40 @@ where
         Until::new(self, until)
     }
 
+    /// Commit to the current branch, see [Cut]
+    fn cut(self) -> Cut<Self, B>
+    where
+        Self: Sized,
+    {
+        Cut::new(self)
+    }
+
+    /// Name the tokenizer in traces
+    fn named(self, name: &'static str) -> Named<Self, B>
+    where
+        Self: Sized,
+    {
+        Named::new(self, name)
+    }
+
+    /// Match between [open] and [close], see [Delimited]
+    fn delimited<O, C>(self, open: O, close: C) -> Delimited<O, Self, C, B>
+    where
+        Self: Sized,
+        O: Tokenizer<'input, B>,
+        C: Tokenizer<'input, B>,
+    {
+        Delimited::new(open, self, close)
+    }
+
+    /// Peek [K] tokens of the sequence, see [Lookahead]
+    fn lookahead<const K: usize>(self) -> Lookahead<Self, K, B>
+    where
+        Self: Sized + Sequence<'input, B>,
+    {
+        Lookahead::new(self)
+    }
+
     fn or<T>(self, other: T) -> Or<Self, T, B>
     where
         Self: Sized,
@@ -104,6 +250,42 @@ where
         Puntuated::new(self, punct)
     }
 
+    /// On failure, report the error and skip input until [sync] matches.
+    /// [sync] is left in the input
+    fn recover_until<S>(self, sync: S) -> Recover<Self, S, B>
+    where
+        Self: Sized,
+        S: Tokenizer<'input, B>,
+    {
+        Recover::until(self, sync)
+    }
+
+    /// On failure, report the error and skip input until and including [sync]
+    fn recover_with<S>(self, sync: S) -> Recover<Self, S, B>
+    where
+        Self: Sized,
+        S: Tokenizer<'input, B>,
+    {
+        Recover::with(self, sync)
+    }
+
+    /// Skip trivia matched by [skip] between the tokens, see [Skipping]
+    fn skipping<S>(self, skip: S) -> Skipping<Self, S, B>
+    where
+        Self: Sized,
+        S: Tokenizer<'input, B>,
+    {
+        Skipping::new(self, skip)
+    }
+
+    /// Do not skip trivia inside the tokenizer, see [NoSkip]
+    fn no_skip(self) -> NoSkip<Self, B>
+    where
+        Self: Sized,
+    {
+        NoSkip::new(self)
+    }
+
     fn slice(self) -> Sliced<Self, B>
     where
         Self: Sized,
@@ -161,6 +343,103 @@ where
     }
 }
 
+pub struct TryMap<T, F, B> {
+    tokenizer: T,
+    func: F,
+    ph: PhantomData<fn(&B)>,
+}
+
+impl<'input, T, F, U, E, B> Tokenizer<'input, B> for TryMap<T, F, B>
+where
+    B: Buffer<'input>,
+    T: Tokenizer<'input, B>,
+    F: Fn(T::Token) -> Result<U, E>,
+    E: Into<Box<dyn core::error::Error + Send + Sync>>,
+{
+    type Token = U;
+
+    fn eat(&self, reader: &mut crate::Reader<'_, 'input, B>) -> Result<(), crate::Error> {
+        self.to_token(reader).map(|_| ())
+    }
+
+    fn to_token(
+        &self,
+        reader: &mut crate::Reader<'_, 'input, B>,
+    ) -> Result<Self::Token, crate::Error> {
+        let start = reader.position();
+        let token = self.tokenizer.to_token(reader)?;
+        let span = Span::new(start, reader.position());
+
+        (self.func)(token).map_err(|err| Error::new(start, err).with_span(span))
+    }
+}
+
+pub struct Verify<T, F, B> {
+    tokenizer: T,
+    pred: F,
+    message: &'static str,
+    ph: PhantomData<fn(&B)>,
+}
+
+impl<'input, T, F, B> Tokenizer<'input, B> for Verify<T, F, B>
+where
+    B: Buffer<'input>,
+    T: Tokenizer<'input, B>,
+    F: Fn(&T::Token) -> bool,
+{
+    type Token = T::Token;
+
+    fn eat(&self, reader: &mut crate::Reader<'_, 'input, B>) -> Result<(), crate::Error> {
+        self.to_token(reader).map(|_| ())
+    }
+
+    fn to_token(
+        &self,
+        reader: &mut crate::Reader<'_, 'input, B>,
+    ) -> Result<Self::Token, crate::Error> {
+        let start = reader.position();
+        let token = self.tokenizer.to_token(reader)?;
+
+        if (self.pred)(&token) {
+            Ok(token)
+        } else {
+            let span = Span::new(start, reader.position());
+            Err(Error::new(start, self.message).with_span(span))
+        }
+    }
+}
+
+pub struct AndThen<T, F, B> {
+    tokenizer: T,
+    func: F,
+    ph: PhantomData<fn(&B)>,
+}
+
+impl<'input, T, F, U, B> Tokenizer<'input, B> for AndThen<T, F, B>
+where
+    B: Buffer<'input>,
+    T: Tokenizer<'input, B>,
+    F: Fn(T::Token, &mut Reader<'_, 'input, B>) -> Result<U, Error>,
+{
+    type Token = U;
+
+    fn eat(&self, reader: &mut crate::Reader<'_, 'input, B>) -> Result<(), crate::Error> {
+        self.to_token(reader).map(|_| ())
+    }
+
+    fn peek(&self, reader: &mut crate::Reader<'_, 'input, B>) -> bool {
+        self.tokenizer.peek(reader)
+    }
+
+    fn to_token(
+        &self,
+        reader: &mut crate::Reader<'_, 'input, B>,
+    ) -> Result<Self::Token, crate::Error> {
+        let token = self.tokenizer.to_token(reader)?;
+        (self.func)(token, reader)
+    }
+}
+
 pub struct MapErr<T, F, B> {
     tokenizer: T,
     func: F,
@@ -179,7 +458,7 @@ where
     fn eat(&self, reader: &mut crate::Reader<'_, 'input, B>) -> Result<(), crate::Error> {
         self.tokenizer
             .eat(reader)
-            .map_err(|err| Error::new(err.position(), (self.func)(err.position(), reader.buffer())))
+            .map_err(|err| self.map(err, reader.buffer()))
     }
 
     fn peek(&self, reader: &mut crate::Reader<'_, 'input, B>) -> bool {
@@ -192,7 +471,28 @@ where
     ) -> Result<Self::Token, crate::Error> {
         self.tokenizer
             .to_token(reader)
-            .map_err(|err| Error::new(err.position(), (self.func)(err.position(), reader.buffer())))
+            .map_err(|err| self.map(err, reader.buffer()))
+    }
+}
+
+impl<T, F, B> MapErr<T, F, B> {
+    fn map<U>(&self, err: Error, buffer: &B) -> Error
+    where
+        F: Fn(usize, &B) -> U,
+        U: Into<Box<dyn core::error::Error + Send + Sync>>,
+    {
+        let committed = err.is_committed();
+        let span = err.span();
+
+        // The original error is kept, so its position and expected items are not lost
+        let error =
+            Error::new_with(span.start, (self.func)(span.start, buffer), vec![err]).with_span(span);
+
+        if committed {
+            error.committed()
+        } else {
+            error
+        }
     }
 }
 
@@ -235,53 +535,96 @@ where
     }
 }
 
-pub struct Repeat<T, B> {
-    tokenizer: T,
-    count: i32,
-    ph: PhantomData<fn(&B)>,
-}
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
 
-impl<'input, T, B> Tokenizer<'input, B> for Repeat<T, B>
-where
-    B: Buffer<'input>,
-    T: Tokenizer<'input, B>,
-{
-    type Token = Item<Vec<T::Token>>;
+    use crate::{tokenizers::Digit, Either, Input};
 
-    fn eat(&self, reader: &mut crate::Reader<'_, 'input, B>) -> Result<(), crate::Error> {
-        let mut count = 0;
-        loop {
-            self.tokenizer.eat(reader)?;
-            count += 1;
-            if count == self.count as usize {
-                break;
-            }
-        }
+    use super::*;
+
+    #[test]
+    fn try_map() {
+        let month =
+            (Digit(10), Digit(10))
+                .slice()
+                .try_map(|m: Item<&str>| match m.value.parse::<u8>() {
+                    Ok(month @ 1..=12) => Ok(month),
+                    _ => Err("invalid month"),
+                });
+
+        let mut input = Input::new("12");
+        assert_eq!(input.parse(&month).unwrap(), 12);
 
-        Ok(())
+        let mut input = Input::new("13");
+        let err = input.parse(&month).unwrap_err();
+        assert_eq!(err.span(), Span::new(0, 2));
+        assert_eq!(err.to_string(), "@0: invalid month");
     }
 
-    fn peek(&self, reader: &mut crate::Reader<'_, 'input, B>) -> bool {
-        self.tokenizer.peek(reader)
+    #[test]
+    fn verify() {
+        let small = Digit(10).verify(|d| d.value < 5, "too large");
+
+        let mut input = Input::new("37");
+        assert_eq!(input.parse(&small).unwrap().value, 3);
+
+        let err = input.parse(&small).unwrap_err();
+        assert_eq!(err.to_string(), "@1: too large");
+        assert!(matches!(
+            input.parse(small.or(Digit(10))),
+            Ok(Either::Right(_))
+        ));
     }
 
-    fn to_token(
-        &self,
-        reader: &mut crate::Reader<'_, 'input, B>,
-    ) -> Result<Self::Token, crate::Error> {
-        let start = reader.position();
+    #[test]
+    fn peek_validates() {
+        let small = Digit(10).verify(|d| d.value < 5, "too large");
+        let month = Digit(10).try_map(|m| match m.value {
+            1..=9 => Ok(m.value),
+            _ => Err("invalid month"),
+        });
+
+        let mut input = Input::new("7");
+        assert!(!input.is(&small));
+        assert!(input.is(&month));
+
+        let mut input = Input::new("0");
+        assert!(!input.is(&month));
+
+        let mut input = Input::new("71x");
+        let digits = input.parse(Digit(10).until(&small)).unwrap();
+        assert_eq!(digits.value.len(), 1);
+        assert_eq!(input.position(), 1);
+    }
 
-        let mut output = Vec::with_capacity(self.count as _);
-        loop {
-            let next = self.tokenizer.parse(reader)?;
-            output.push(next);
-            if output.len() == self.count as usize {
-                break;
+    #[test]
+    fn and_then() {
+        let pair = Digit(10).and_then(|first, reader| {
+            let second = reader.parse(Digit(10))?;
+            if first.value == second.value {
+                Ok(first.value)
+            } else {
+                Err(reader.error("mismatched pair"))
             }
-        }
+        });
 
-        let end = reader.position();
+        let mut input = Input::new("1123");
+        assert_eq!(input.parse(&pair).unwrap(), 1);
+        assert!(input.parse(&pair).is_err());
+        assert_eq!(input.parse((Digit(10), Digit(10))).unwrap().1.value, 3);
+    }
 
-        Ok(Item::new(Span::new(start, end), output))
+    #[test]
+    fn map_err_keeps_error() {
+        let digit = Digit(10).map_err(|_, _| "expected a number");
+
+        let mut input = Input::new("x");
+        let err = input.parse(&digit).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "@0: expected a number, errors: @0: expected digit, found `x`"
+        );
+        assert_eq!(err.span(), Span::new(0, 1));
     }
 }
@@ -1,8 +1,14 @@
-use crate::{buffer::IntoBuffer, cursor::Cursor, Buffer, Reader, Result, Tokenizer};
+use alloc::vec::Vec;
+
+use crate::{
+    buffer::IntoBuffer, cursor::Cursor, state::State, AsSlice, Buffer, Checkpoint, Error, Reader,
+    Result, Span, Tokenizer,
+};
 
 pub struct Input<B> {
     buffer: B,
     index: usize,
+    state: State,
 }
 
 impl Input<()> {
@@ -10,6 +16,7 @@ impl Input<()> {
         Input {
             buffer: buffer.into_buffer(),
             index: 0,
+            state: State::default(),
         }
     }
 }
@@ -19,19 +26,111 @@ impl<B> Input<B> {
         &self.buffer
     }
 
+    /// Limit how deep tokenizers can nest, to protect the stack against deeply nested input.
+    /// Each tokenizer run through a reader counts as a level
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.state.max_depth = Some(depth);
+        self
+    }
+
+    /// Limit how many tokenizers a single parse can run,
+    /// to bound the time spent backtracking
+    pub fn with_max_steps(mut self, steps: usize) -> Self {
+        self.state.max_steps = Some(steps);
+        self
+    }
+
+    /// Trace the tokenizers run by the readers of this input
+    #[cfg(feature = "trace")]
+    pub fn with_tracer<T: crate::Trace + 'static>(mut self, tracer: T) -> Self {
+        self.state.tracer = Some(alloc::boxed::Box::new(tracer));
+        self
+    }
+
+    /// Errors reported by recovering tokenizers
+    pub fn diagnostics(&self) -> &[Error] {
+        self.state.diagnostics()
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<Error> {
+        self.state.take_diagnostics()
+    }
+
+    /// The current position in the source
+    pub fn position<'input>(&self) -> usize
+    where
+        B: Buffer<'input>,
+    {
+        self.buffer
+            .get(self.index)
+            .map(|m| m.index)
+            .unwrap_or_else(|| self.buffer.end_position())
+    }
+
+    /// Move to [position] in the source.
+    /// Fails if no item starts at [position]
+    pub fn set_position<'input>(&mut self, position: usize) -> Result<()>
+    where
+        B: Buffer<'input>,
+    {
+        let Some(index) = self.buffer.index_of(position) else {
+            return Err(Error::new(
+                position,
+                "position is not at the start of an item",
+            ));
+        };
+
+        self.index = index;
+        Ok(())
+    }
+
+    /// The source from the current position to the end.
+    /// None once all input is consumed, as empty spans do not slice
+    pub fn remaining<'input>(&self) -> Option<<B::Source as AsSlice<'input>>::Slice>
+    where
+        B: Buffer<'input>,
+        B::Source: AsSlice<'input>,
+    {
+        let span = Span::new(self.position(), self.buffer.end_position());
+        self.buffer.source().sliced(span)
+    }
+
+    /// Move back to the start of the input
+    pub fn reset(&mut self) {
+        self.index = 0;
+    }
+
+    /// Save the current state, see [Reader::checkpoint]
+    pub fn checkpoint<'input>(&mut self) -> Checkpoint
+    where
+        B: Buffer<'input>,
+    {
+        self.reader().checkpoint()
+    }
+
+    /// Move back to [checkpoint], dropping errors reported after it
+    pub fn rewind<'input>(&mut self, checkpoint: Checkpoint)
+    where
+        B: Buffer<'input>,
+    {
+        self.reader().rewind(checkpoint)
+    }
+
     #[inline(always)]
     fn reader<'this, 'input>(&'this mut self) -> Reader<'this, 'input, B>
     where
         B: Buffer<'input>,
     {
-        Reader::new(Cursor::new(&mut self.index, &mut self.buffer))
+        Reader::new(Cursor::new(&mut self.index, &self.buffer, &mut self.state))
     }
 
     pub fn is<'input, T: Tokenizer<'input, B>>(&mut self, tokenizer: T) -> bool
     where
         B: Buffer<'input>,
     {
-        self.reader().is(tokenizer)
+        let ret = self.reader().is(tokenizer);
+        self.state.reset();
+        ret
     }
 
     /// Parse a
@@ -39,7 +138,8 @@ impl<B> Input<B> {
     where
         B: Buffer<'input>,
     {
-        self.reader().parse(tokenizer)
+        let ret = self.reader().parse(tokenizer);
+        self.finish(ret)
     }
 
     /// Eat a token
@@ -47,6 +147,115 @@ impl<B> Input<B> {
     where
         B: Buffer<'input>,
     {
-        self.reader().eat(tokenizer)
+        let ret = self.reader().eat(tokenizer);
+        self.finish(ret)
+    }
+
+    fn finish<T>(&mut self, result: Result<T>) -> Result<T> {
+        match result {
+            Ok(ret) => {
+                let exceeded = self.state.exceeded();
+                self.state.reset();
+
+                // A limit was exceeded while peeking, so the result can not be trusted
+                match exceeded {
+                    Some(err) => Err(err),
+                    None => Ok(ret),
+                }
+            }
+            Err(err) => Err(self.state.finish(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use crate::{tokenizers::Digit, ErrorKind, IntoTokenizer, Limit, Reader, Span, TokenizerExt};
+
+    use super::*;
+
+    fn nested<'input, B>(reader: &mut Reader<'_, 'input, B>) -> Result<()>
+    where
+        B: Buffer<'input>,
+        B::Item: crate::AsChar,
+    {
+        reader.eat(('[', nested.into_tokenizer().optional(), ']'))
+    }
+
+    #[test]
+    fn max_depth() {
+        let source = "[".repeat(1000) + &"]".repeat(1000);
+
+        let mut input = Input::new(source.as_str()).with_max_depth(100);
+        let err = input.eat(nested.into_tokenizer()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Limit(Limit::Depth(100))));
+
+        let mut input = Input::new("[[]]").with_max_depth(100);
+        assert!(input.eat(nested.into_tokenizer()).is_ok());
+    }
+
+    #[test]
+    fn positions() {
+        let mut input = Input::new("ab€d");
+
+        input.eat(('a', 'b')).unwrap();
+        assert_eq!(input.position(), 2);
+        assert_eq!(input.remaining(), Some("€d"));
+
+        assert!(input.set_position(3).is_err());
+        input.set_position(5).unwrap();
+        assert_eq!(input.parse('d').unwrap().span, Span::new(5, 6));
+        assert_eq!(input.remaining(), None);
+
+        input.reset();
+        assert_eq!(input.position(), 0);
+    }
+
+    #[test]
+    fn checkpoints() {
+        fn pair<'input, B>(reader: &mut Reader<'_, 'input, B>) -> Result<u32>
+        where
+            B: Buffer<'input>,
+            B::Item: crate::AsChar,
+        {
+            let checkpoint = reader.checkpoint();
+            let first = reader.parse(Digit(10))?;
+
+            match reader.parse(Digit(10)) {
+                Ok(second) => Ok(first.value * 10 + second.value),
+                Err(_) => {
+                    reader.rewind(checkpoint);
+                    reader.report(reader.error("single digit"));
+                    reader.rewind(checkpoint);
+                    Ok(reader.parse(Digit(10))?.value)
+                }
+            }
+        }
+
+        let mut input = Input::new("12 3");
+        assert_eq!(input.parse(pair.into_tokenizer()).unwrap(), 12);
+        input.eat(' ').unwrap();
+        assert_eq!(input.parse(pair.into_tokenizer()).unwrap(), 3);
+        assert!(input.diagnostics().is_empty());
+
+        let checkpoint = input.checkpoint();
+        input.set_position(0).unwrap();
+        input.rewind(checkpoint);
+        assert_eq!(input.position(), 4);
+    }
+
+    #[test]
+    fn max_steps() {
+        let mut input = Input::new("1234567890").with_max_steps(5);
+
+        // Not swallowed by the repetition
+        let err = input.parse(Digit(10).many()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Limit(Limit::Steps(5))));
+        assert!(err.to_string().ends_with("step limit of 5 exceeded"));
+
+        // The budget is per parse
+        assert!(input.parse(Digit(10)).is_ok());
     }
 }
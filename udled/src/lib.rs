@@ -6,6 +6,7 @@ pub mod buffer;
 #[cfg(feature = "binary")]
 pub mod bytes;
 mod cursor;
+mod diagnostic;
 mod either;
 mod error;
 mod ext;
@@ -14,9 +15,16 @@ mod into_tokenizer;
 mod item;
 mod location;
 mod macros;
+mod memo;
 mod reader;
+mod source_map;
 mod span;
+mod state;
+#[cfg(test)]
+mod test_alloc;
 mod tokenizer;
+#[cfg(feature = "trace")]
+mod trace;
 mod traits;
 
 pub mod tokenizers;
@@ -25,19 +33,24 @@ pub use self::into_tokenizer::IntoTokenizer;
 
 pub use self::{
     buffer::{Buffer, BufferItem},
+    diagnostic::{Diagnostic, Label, Renderer, Severity},
     either::Either,
     error::*,
     ext::TokenizerExt,
     input::Input,
     item::Item,
-    location::Location,
-    reader::Reader,
+    location::{ColumnUnit, LineIndex, Location},
+    reader::{Checkpoint, Reader},
+    source_map::{FileId, ResolvedSpan, SourceFile, SourceMap},
     span::*,
     tokenizer::{Char, Tokenizer, EOF},
     tokenizers::Next,
     traits::*,
 };
 
+#[cfg(feature = "trace")]
+pub use self::trace::{Outcome, Trace, TraceKind, TraceTree};
+
 #[cfg(feature = "macros")]
 pub use udled_macros::visitor;
 
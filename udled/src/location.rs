@@ -1,3 +1,7 @@
+use alloc::vec::Vec;
+
+use crate::{Span, StringExt};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Location {
     pub line: usize,
@@ -11,43 +15,274 @@ impl Location {
 }
 
 fn byte_to_line(input: &str, pos: usize) -> Option<Location> {
-    let mut line = 0;
-    if !input.is_char_boundary(pos) || pos > input.len() {
+    if pos > input.len() || !input.is_char_boundary(pos) {
         return None;
     }
 
-    let mut row = 0;
+    let mut line = 0;
+    let mut start = 0;
+
+    for (_, next) in line_breaks(input).take_while(|m| m.1 <= pos) {
+        line += 1;
+        start = next;
+    }
+
+    Some(Location {
+        line,
+        column: input[start..pos].chars().count(),
+    })
+}
+
+/// Iterate line breaks as (start of line break, start of next line).
+/// "\r\n" counts as a single line break
+fn line_breaks(input: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
     let mut chars = input.char_indices().peekable();
 
-    loop {
-        let Some((idx, char)) = chars.next() else {
-            break;
-        };
+    core::iter::from_fn(move || loop {
+        let (idx, char) = chars.next()?;
+
+        if !char.is_linebreak() {
+            continue;
+        }
+
+        if char == '\r' && chars.peek().map(|m| m.1) == Some('\n') {
+            chars.next();
+            return Some((idx, idx + 2));
+        }
+
+        return Some((idx, idx + char.len_utf8()));
+    })
+}
+
+/// The unit columns are counted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColumnUnit {
+    /// UTF-8 bytes
+    Utf8,
+    /// Unicode scalar values
+    Char,
+    /// UTF-16 code units, as used by the language server protocol
+    Utf16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WideChar {
+    offset: usize,
+    len_utf8: u8,
+    len_utf16: u8,
+}
+
+impl WideChar {
+    fn len(&self, unit: ColumnUnit) -> usize {
+        match unit {
+            ColumnUnit::Utf8 => self.len_utf8 as usize,
+            ColumnUnit::Char => 1,
+            ColumnUnit::Utf16 => self.len_utf16 as usize,
+        }
+    }
+}
+
+/// Maps byte offsets in a source to lines and columns, and back.
+/// Build it once per source; lookups are binary searches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// The content of each line, excluding the line break
+    lines: Vec<Span>,
+    /// All multi-byte chars, ordered by offset
+    wide: Vec<WideChar>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> LineIndex {
+        let mut lines = Vec::new();
+        let mut start = 0;
+
+        for (end, next) in line_breaks(source) {
+            lines.push(Span::new(start, end));
+            start = next;
+        }
+
+        lines.push(Span::new(start, source.len()));
+
+        let wide = source
+            .char_indices()
+            .filter(|(_, char)| !char.is_ascii())
+            .map(|(offset, char)| WideChar {
+                offset,
+                len_utf8: char.len_utf8() as u8,
+                len_utf16: char.len_utf16() as u8,
+            })
+            .collect();
 
-        if idx == pos {
-            break;
+        LineIndex {
+            lines,
+            wide,
+            len: source.len(),
         }
+    }
+
+    /// Number of lines. A source always has at least one line
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The span of [line], excluding the line break
+    pub fn line_span(&self, line: usize) -> Option<Span> {
+        self.lines.get(line).copied()
+    }
 
-        let mut is_ws = char == '\n';
-        if !is_ws && char == '\r' {
-            if chars.peek().map(|m| m.1) == Some('\n') {
-                let _ = chars.next();
-                is_ws = true;
+    /// The line containing [offset]
+    pub fn line(&self, offset: usize) -> Option<usize> {
+        if offset > self.len || self.inside_char(offset) {
+            return None;
+        }
+
+        Some(
+            self.lines
+                .partition_point(|line| line.start <= offset)
+                .saturating_sub(1),
+        )
+    }
+
+    /// Location of [offset] with the column counted in chars, like [Location::from]
+    pub fn location(&self, offset: usize) -> Option<Location> {
+        self.location_in(offset, ColumnUnit::Char)
+    }
+
+    /// Location of [offset] with the column counted in [unit]
+    pub fn location_in(&self, offset: usize, unit: ColumnUnit) -> Option<Location> {
+        let line = self.line(offset)?;
+        let start = self.lines[line].start;
+
+        let column = offset
+            - start
+            - self
+                .wide_chars(start, offset)
+                .iter()
+                .map(|m| m.len_utf8 as usize - m.len(unit))
+                .sum::<usize>();
+
+        Some(Location { line, column })
+    }
+
+    /// Byte offset of [location], with the column counted in chars
+    pub fn offset(&self, location: Location) -> Option<usize> {
+        self.offset_in(location, ColumnUnit::Char)
+    }
+
+    /// Byte offset of [location], with the column counted in [unit].
+    /// Returns None if the location is outside the line or inside a char
+    pub fn offset_in(&self, location: Location, unit: ColumnUnit) -> Option<usize> {
+        let line = self.lines.get(location.line)?;
+
+        let mut offset = line.start + location.column;
+
+        for wide in self.wide_chars(line.start, line.end) {
+            if wide.offset >= offset {
+                break;
+            }
+
+            let len = wide.len(unit);
+            if wide.offset + len > offset {
+                // The column points inside this char
+                return None;
             }
+
+            offset += wide.len_utf8 as usize - len;
         }
 
-        if is_ws {
-            line += 1;
-            row = 0;
-        } else {
-            row += 1;
+        if offset > line.end {
+            return None;
         }
+
+        Some(offset)
+    }
+
+    /// Start and end of [span] as locations
+    pub fn span_location(&self, span: Span) -> Option<(Location, Location)> {
+        Some((self.location(span.start)?, self.location(span.end)?))
     }
 
-    Some(Location { line, column: row })
+    fn wide_chars(&self, start: usize, end: usize) -> &[WideChar] {
+        let from = self.wide.partition_point(|m| m.offset < start);
+        let to = self.wide.partition_point(|m| m.offset < end);
+        &self.wide[from..to]
+    }
+
+    fn inside_char(&self, offset: usize) -> bool {
+        let idx = self.wide.partition_point(|m| m.offset < offset);
+        idx > 0 && {
+            let wide = self.wide[idx - 1];
+            offset < wide.offset + wide.len_utf8 as usize
+        }
+    }
 }
 
-// pub(crate) const fn is_utf8_char_boundary(this: u8) -> bool {
-//     // This is bit magic equivalent to: b < 128 || b >= 192
-//     (this as i8) >= -0x40
-// }
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn loc(line: usize, column: usize) -> Location {
+        Location { line, column }
+    }
+
+    #[test]
+    fn line_breaks() {
+        let source = "a\nb\r\nc\rd\u{2028}e\u{2029}f";
+        let index = LineIndex::new(source);
+
+        assert_eq!(index.line_count(), 6);
+
+        for (offset, char) in source.char_indices() {
+            if char.is_linebreak() {
+                continue;
+            }
+            assert_eq!(index.location(offset), Location::from(source, offset));
+        }
+
+        assert_eq!(index.location(2), Some(loc(1, 0)));
+        assert_eq!(index.location(5), Some(loc(2, 0)));
+        assert_eq!(index.location(7), Some(loc(3, 0)));
+        assert_eq!(index.location(11), Some(loc(4, 0)));
+        assert_eq!(index.location(15), Some(loc(5, 0)));
+        assert_eq!(index.line_span(1), Some(Span::new(2, 3)));
+    }
+
+    #[test]
+    fn columns() {
+        let source = "x\naæ😀b";
+        let index = LineIndex::new(source);
+
+        // 'b' is at byte 9
+        assert_eq!(index.location_in(9, ColumnUnit::Utf8), Some(loc(1, 7)));
+        assert_eq!(index.location_in(9, ColumnUnit::Char), Some(loc(1, 3)));
+        assert_eq!(index.location_in(9, ColumnUnit::Utf16), Some(loc(1, 4)));
+
+        assert_eq!(index.offset_in(loc(1, 7), ColumnUnit::Utf8), Some(9));
+        assert_eq!(index.offset_in(loc(1, 3), ColumnUnit::Char), Some(9));
+        assert_eq!(index.offset_in(loc(1, 4), ColumnUnit::Utf16), Some(9));
+
+        // Inside the emoji
+        assert_eq!(index.location(6), None);
+        assert_eq!(index.offset_in(loc(1, 3), ColumnUnit::Utf16), None);
+        // Past the end of the line
+        assert_eq!(index.offset(loc(0, 2)), None);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let source = "fn main() {\r\n    let å = \"ø\";\n}\n";
+        let index = LineIndex::new(source);
+
+        for (offset, _) in source.char_indices() {
+            for unit in [ColumnUnit::Utf8, ColumnUnit::Char, ColumnUnit::Utf16] {
+                let location = index.location_in(offset, unit).unwrap();
+                if source[offset..].starts_with('\n') && source[..offset].ends_with('\r') {
+                    continue;
+                }
+                assert_eq!(index.offset_in(location, unit), Some(offset));
+            }
+        }
+    }
+}
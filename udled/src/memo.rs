@@ -0,0 +1,38 @@
+use core::any::{Any, TypeId};
+
+use alloc::{boxed::Box, collections::BTreeMap};
+
+use crate::Error;
+
+/// Identifies a memoized rule, by its id and token type, at a buffer index
+pub(crate) type MemoKey = (&'static str, TypeId, usize);
+
+pub(crate) struct MemoEntry {
+    /// Buffer index after the rule
+    pub end: usize,
+    pub result: Result<Box<dyn Any>, Error>,
+}
+
+/// Results of memoized rules, keyed by rule and buffer index
+#[derive(Default)]
+pub(crate) struct Memo {
+    entries: BTreeMap<MemoKey, MemoEntry>,
+}
+
+impl Memo {
+    pub fn get(&self, key: &MemoKey) -> Option<&MemoEntry> {
+        self.entries.get(key)
+    }
+
+    pub fn insert(&mut self, key: MemoKey, entry: MemoEntry) {
+        self.entries.insert(key, entry);
+    }
+
+    pub fn remove(&mut self, key: &MemoKey) {
+        self.entries.remove(key);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -1,12 +1,34 @@
-use alloc::{boxed::Box, vec::Vec};
+use core::any::{Any, TypeId};
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 use crate::{
     buffer::Buffer,
     cursor::Cursor,
-    error::{Error, Result},
+    error::{Error, Expected, Found, Result},
+    memo::{MemoEntry, MemoKey},
     tokenizer::Tokenizer,
+    tokenizers::Skip,
+    Item, Span,
 };
 
+#[cfg(feature = "trace")]
+use crate::trace::{self, Outcome, TraceKind};
+#[cfg(feature = "trace")]
+use alloc::borrow::Cow;
+
+/// A saved state of a reader, to rewind to with [Reader::rewind]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    index: usize,
+    diagnostics: usize,
+}
+
 pub struct Reader<'a, 'input, B> {
     cursor: Cursor<'a, 'input, B>,
 }
@@ -35,6 +57,40 @@ where
         Error::new_with(self.cursor.prev_position(), error, errors)
     }
 
+    /// Create an error expecting [expected] at the current position.
+    /// The failure is tracked, so the furthest failure of a parse can be reported
+    #[inline]
+    pub fn expected<E: Into<Expected>>(&mut self, expected: E) -> Error {
+        self.cursor.expected(expected.into())
+    }
+
+    /// Like [Reader::expected], but replaces the failures tracked at the current position
+    #[inline]
+    pub(crate) fn expected_only<E: Into<Expected>>(&mut self, expected: E) -> Error {
+        let error = self.cursor.expected(expected.into());
+        self.cursor.state().relabel(&error);
+        error
+    }
+
+    /// Track [error] as a failure, so it is merged into the furthest failure
+    #[inline]
+    pub(crate) fn track(&mut self, error: &Error) {
+        self.cursor.state().fail(error);
+    }
+
+    /// Report an error and continue parsing.
+    /// Reports made by a tokenizer that later fails, or while peeking, are dropped
+    #[inline]
+    pub fn report(&mut self, error: Error) {
+        self.cursor.state().report(error);
+    }
+
+    /// Errors reported so far
+    #[inline]
+    pub fn diagnostics(&self) -> &[Error] {
+        self.cursor.state_ref().diagnostics()
+    }
+
     #[inline]
     pub fn position(&self) -> usize {
         self.cursor.position()
@@ -45,6 +101,24 @@ where
         self.cursor.buffer()
     }
 
+    /// Save the current state, to try several steps and rewind if one fails
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            index: self.cursor.index(),
+            diagnostics: self.cursor.state_ref().diagnostics().len(),
+        }
+    }
+
+    /// Move back to [checkpoint], dropping errors reported after it
+    #[inline]
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.cursor.set_index(checkpoint.index);
+        self.cursor
+            .state()
+            .truncate_diagnostics(checkpoint.diagnostics);
+    }
+
     #[inline]
     pub fn read(&mut self) -> Result<B::Item> {
         let Some(ch) = self.cursor.eat() else {
@@ -53,6 +127,68 @@ where
         Ok(ch.item)
     }
 
+    /// Read the next item with its span in the source
+    #[inline]
+    pub fn read_item(&mut self) -> Result<Item<B::Item>> {
+        let Some(ch) = self.cursor.eat() else {
+            return Err(Error::new(self.position(), "EOF"));
+        };
+        Ok(Item::new(Span::new(ch.index, ch.index + ch.len), ch.item))
+    }
+
+    /// The items read since [checkpoint]
+    pub(crate) fn items_since(
+        &self,
+        checkpoint: &Checkpoint,
+    ) -> impl Iterator<Item = B::Item> + '_ {
+        let buffer = self.cursor.buffer();
+        let end = self.cursor.index();
+        let mut idx = checkpoint.index;
+
+        core::iter::from_fn(move || {
+            if idx >= end {
+                return None;
+            }
+            let item = buffer.get(idx)?;
+            idx = buffer.next_index(idx);
+            Some(item.item)
+        })
+    }
+
+    /// Describe the items read since [checkpoint] for error messages.
+    /// Chars are quoted together, like `begin`, tokens are separated by spaces
+    pub(crate) fn describe_since(&self, checkpoint: &Checkpoint) -> Option<String> {
+        let buffer = self.cursor.buffer();
+        let mut items = Vec::new();
+
+        let mut idx = checkpoint.index;
+        while idx < self.cursor.index() {
+            items.push(buffer.describe(idx)?);
+            idx = buffer.next_index(idx);
+        }
+
+        let chars = items
+            .iter()
+            .map(|m| match m {
+                Found::Char(char) => Some(*char),
+                _ => None,
+            })
+            .collect::<Option<String>>();
+
+        match (items.as_slice(), chars) {
+            ([], _) => None,
+            ([item], _) => Some(item.to_string()),
+            (_, Some(chars)) => Some(format!("`{chars}`")),
+            (items, None) => Some(
+                items
+                    .iter()
+                    .map(|m| m.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+        }
+    }
+
     /// Peek char at current position
     #[inline]
     pub fn peek_ch(&mut self) -> Option<B::Item> {
@@ -67,30 +203,304 @@ where
 
     #[inline]
     pub fn is<T: Tokenizer<'input, B>>(&mut self, tokenizer: T) -> bool {
-        self.cursor.child_peek(|cursor| {
-            let mut reader = Reader { cursor };
-            tokenizer.peek(&mut reader)
-        })
+        #[cfg(feature = "trace")]
+        let name = self.trace_enter(TraceKind::Peek, &tokenizer);
+
+        let ret = self
+            .cursor
+            .child_peek(|cursor| {
+                let mut reader = Reader { cursor };
+                reader.skip().is_ok() && tokenizer.peek(&mut reader)
+            })
+            .unwrap_or(false);
+
+        #[cfg(feature = "trace")]
+        self.trace_exit(TraceKind::Peek, name, ret);
+
+        ret
+    }
+
+    /// Like [Reader::is], but failures are not tracked.
+    /// Used for negative lookahead
+    #[inline]
+    pub(crate) fn is_silent<T: Tokenizer<'input, B>>(&mut self, tokenizer: T) -> bool {
+        self.cursor.state().enter_silent();
+        let ret = self.is(tokenizer);
+        self.cursor.state().leave_silent();
+        ret
     }
 
     /// Parse a token
     #[inline]
     pub fn parse<T: Tokenizer<'input, B>>(&mut self, tokenizer: T) -> Result<T::Token> {
-        self.cursor.child(|cursor| {
+        self.attempt(tokenizer).0
+    }
+
+    /// Parse a token in a single pass. On failure the input is rewound.
+    /// Returned with the result is whether the tokenizer consumed input before it failed
+    #[inline]
+    pub(crate) fn attempt<T: Tokenizer<'input, B>>(
+        &mut self,
+        tokenizer: T,
+    ) -> (Result<T::Token>, bool) {
+        #[cfg(feature = "trace")]
+        let name = self.trace_enter(TraceKind::Parse, &tokenizer);
+
+        let ret = self.run(|reader| tokenizer.to_token(reader));
+
+        #[cfg(feature = "trace")]
+        self.trace_exit(TraceKind::Parse, name, ret.0.is_ok());
+
+        ret
+    }
+
+    /// Like [Reader::attempt], but eats the token
+    #[inline]
+    pub(crate) fn attempt_eat<T: Tokenizer<'input, B>>(
+        &mut self,
+        tokenizer: T,
+    ) -> (Result<()>, bool) {
+        #[cfg(feature = "trace")]
+        let name = self.trace_enter(TraceKind::Eat, &tokenizer);
+
+        let ret = self.run(|reader| tokenizer.eat(reader));
+
+        #[cfg(feature = "trace")]
+        self.trace_exit(TraceKind::Eat, name, ret.0.is_ok());
+
+        ret
+    }
+
+    /// Run [func] in a child reader after skipping trivia
+    #[inline]
+    fn run<F, R>(&mut self, func: F) -> (Result<R>, bool)
+    where
+        F: FnOnce(&mut Reader<'_, 'input, B>) -> Result<R>,
+    {
+        let mut consumed = false;
+
+        let ret = self.cursor.child(|cursor| {
             let mut reader = Reader { cursor };
+            reader.skip()?;
+
+            let start = reader.position();
+            func(&mut reader).inspect_err(|err| {
+                consumed = reader.position() > start || err.position() > start;
+            })
+        });
+
+        (ret, consumed)
+    }
+
+    /// Parse a token, reusing the result if the rule [id] was already parsed at this position
+    pub(crate) fn parse_memoized<T>(&mut self, id: &'static str, tokenizer: &T) -> Result<T::Token>
+    where
+        T: Tokenizer<'input, B>,
+        T::Token: Clone + 'static,
+    {
+        let key = (id, TypeId::of::<T::Token>(), self.cursor.index());
+
+        if let Some(ret) = self.memo_lookup::<T::Token>(&key) {
+            return ret;
+        }
+
+        let reported = self.cursor.state_ref().diagnostics().len();
+        let ret = self.parse(tokenizer);
+
+        // Reported diagnostics would not be replayed, so the result is not reusable
+        if self.cursor.state_ref().diagnostics().len() != reported {
+            return ret;
+        }
+
+        let result = match &ret {
+            Ok(token) => Ok(Box::new(token.clone()) as Box<dyn Any>),
+            Err(err) => match err.try_clone() {
+                Some(err) => Err(err),
+                None => return ret,
+            },
+        };
+
+        let end = self.cursor.index();
+        self.cursor
+            .state()
+            .memo_mut()
+            .insert(key, MemoEntry { end, result });
+
+        ret
+    }
+
+    /// Parse a directly left recursive rule by growing a seed.
+    /// The rule first fails at this position, then it is parsed again with its
+    /// last result, as long as it gets further
+    pub(crate) fn parse_left_recursive<T>(
+        &mut self,
+        id: &'static str,
+        tokenizer: &T,
+    ) -> Result<T::Token>
+    where
+        T: Tokenizer<'input, B>,
+        T::Token: Clone + 'static,
+    {
+        let start = self.cursor.index();
+        let key = (id, TypeId::of::<T::Token>(), start);
+
+        if let Some(ret) = self.memo_lookup::<T::Token>(&key) {
+            return ret;
+        }
+
+        let position = self.position();
+        let seed = Error::expected_one_of(Span::new(position, position), []);
+
+        self.cursor.state().memo_mut().insert(
+            key,
+            MemoEntry {
+                end: start,
+                result: Err(seed),
+            },
+        );
 
-            let token = tokenizer.to_token(&mut reader)?;
+        let mut best: Option<(T::Token, usize)> = None;
 
-            Ok(token)
+        let error = loop {
+            let ret = self.parse(tokenizer);
+            let end = self.cursor.index();
+            self.cursor.set_index(start);
+
+            match ret {
+                Ok(token) if best.as_ref().map_or(true, |m| end > m.1) => {
+                    self.cursor.state().memo_mut().insert(
+                        key,
+                        MemoEntry {
+                            end,
+                            result: Ok(Box::new(token.clone())),
+                        },
+                    );
+                    best = Some((token, end));
+                }
+                Ok(_) => break None,
+                Err(err) if err.is_committed() || best.is_none() => break Some(err),
+                Err(_) => break None,
+            }
+        };
+
+        match (best, error) {
+            (Some((token, end)), None) => {
+                self.cursor.set_index(end);
+                Ok(token)
+            }
+            (_, error) => {
+                let error = error.unwrap_or_else(|| Error::new(position, "left recursion"));
+                match error.try_clone() {
+                    Some(err) => self.cursor.state().memo_mut().insert(
+                        key,
+                        MemoEntry {
+                            end: start,
+                            result: Err(err),
+                        },
+                    ),
+                    None => self.cursor.state().memo_mut().remove(&key),
+                }
+                Err(error)
+            }
+        }
+    }
+
+    fn memo_lookup<T: Clone + 'static>(&mut self, key: &MemoKey) -> Option<Result<T>> {
+        let entry = self.cursor.state_ref().memo().get(key)?;
+
+        match &entry.result {
+            Ok(token) => {
+                let token = token.downcast_ref::<T>()?.clone();
+                let end = entry.end;
+                self.cursor.set_index(end);
+                Some(Ok(token))
+            }
+            Err(err) => err.try_clone().map(Err),
+        }
+    }
+
+    /// Run [func] without skipping trivia, so its tokens are read as one lexeme.
+    /// See [Skipping](crate::tokenizers::Skipping)
+    pub fn no_skip<F, R>(&mut self, func: F) -> Result<R>
+    where
+        F: FnOnce(&mut Reader<'_, 'input, B>) -> Result<R>,
+    {
+        self.with_skipper(None, func)
+    }
+
+    /// Run [func], skipping trivia with [skip] before each token
+    pub(crate) fn with_skipper<F, R>(
+        &mut self,
+        skip: Option<&(dyn Skip<'input, B> + '_)>,
+        func: F,
+    ) -> Result<R>
+    where
+        F: FnOnce(&mut Reader<'_, 'input, B>) -> Result<R>,
+    {
+        self.cursor.child_with(skip, |cursor| {
+            let mut reader = Reader { cursor };
+            func(&mut reader)
+        })
+    }
+
+    /// Skip trivia, if a skipper is set
+    pub(crate) fn skip(&mut self) -> Result<()> {
+        let Some(skip) = self.cursor.skip() else {
+            return Ok(());
+        };
+
+        self.cursor.child_with(None, |cursor| {
+            let mut reader = Reader { cursor };
+            skip.skip(&mut reader)
         })
     }
 
     /// Eat a token
     #[inline]
     pub fn eat<T: Tokenizer<'input, B>>(&mut self, tokenizer: T) -> Result<()> {
-        self.cursor.child(|cursor| {
-            let mut reader = Reader { cursor };
-            tokenizer.eat(&mut reader)
-        })
+        self.attempt_eat(tokenizer).0
+    }
+}
+
+#[cfg(feature = "trace")]
+impl<'a, 'input, B> Reader<'a, 'input, B>
+where
+    B: Buffer<'input>,
+{
+    /// Returns the name of the tokenizer, if tracing
+    fn trace_enter<T: Tokenizer<'input, B>>(
+        &mut self,
+        kind: TraceKind,
+        tokenizer: &T,
+    ) -> Option<Cow<'static, str>> {
+        let position = self.position();
+        let tracer = self.cursor.state().tracer.as_mut()?;
+
+        let name = match tokenizer.name() {
+            Some(name) => Cow::Owned(name.into()),
+            None => trace::type_name::<T>(),
+        };
+
+        tracer.enter(kind, &name, position);
+
+        Some(name)
+    }
+
+    fn trace_exit(&mut self, kind: TraceKind, name: Option<Cow<'static, str>>, matched: bool) {
+        let Some(name) = name else {
+            return;
+        };
+
+        let outcome = if matched {
+            Outcome::Matched {
+                end: self.position(),
+            }
+        } else {
+            Outcome::Failed
+        };
+
+        if let Some(tracer) = self.cursor.state().tracer.as_mut() {
+            tracer.exit(kind, &name, outcome);
+        }
     }
 }
@@ -0,0 +1,225 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{Diagnostic, LineIndex, Location, Renderer, Span, WithSpan};
+
+/// Identifies a file in a [SourceMap]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(usize);
+
+impl FileId {
+    pub const fn index(&self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    id: FileId,
+    name: String,
+    source: String,
+    /// Offset of the file in the global span space
+    base: usize,
+    index: LineIndex,
+}
+
+impl SourceFile {
+    pub fn id(&self) -> FileId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn index(&self) -> &LineIndex {
+        &self.index
+    }
+
+    /// The global span covering the whole file
+    pub fn span(&self) -> Span {
+        Span::new(self.base, self.base + self.source.len())
+    }
+
+    /// Convert a span local to this file into the global span space
+    pub fn global(&self, local: Span) -> Span {
+        Span::new(local.start + self.base, local.end + self.base)
+    }
+
+    /// Convert a global span into a span local to this file
+    pub fn local(&self, global: Span) -> Span {
+        Span::new(
+            global.start.saturating_sub(self.base),
+            global.end.saturating_sub(self.base),
+        )
+    }
+}
+
+/// A span resolved to the file it points into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedSpan<'a> {
+    pub file: FileId,
+    pub name: &'a str,
+    /// The span, local to the file
+    pub span: Span,
+    pub start: Location,
+    pub end: Location,
+    pub slice: &'a str,
+}
+
+/// A set of source files sharing one span space.
+///
+/// Each file is given its own range of offsets, so a global span identifies
+/// both the file and the position in it. Parse a file with its local source,
+/// and convert spans with [SourceMap::span] or [SourceFile::global]
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap::default()
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, source: impl Into<String>) -> FileId {
+        let id = FileId(self.files.len());
+        let source = source.into();
+
+        // Leave a gap, so the end of a file is not the start of the next
+        let base = self
+            .files
+            .last()
+            .map(|m| m.span().end + 1)
+            .unwrap_or_default();
+
+        self.files.push(SourceFile {
+            id,
+            name: name.into(),
+            index: LineIndex::new(&source),
+            source,
+            base,
+        });
+
+        id
+    }
+
+    pub fn file(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get(id.0)
+    }
+
+    pub fn files(&self) -> core::slice::Iter<'_, SourceFile> {
+        self.files.iter()
+    }
+
+    /// Convert a span local to [file] into the global span space
+    pub fn span(&self, file: FileId, local: Span) -> Span {
+        match self.file(file) {
+            Some(file) => file.global(local),
+            None => local,
+        }
+    }
+
+    /// Find the file containing the global [span]
+    pub fn lookup(&self, span: Span) -> Option<&SourceFile> {
+        let idx = self
+            .files
+            .partition_point(|m| m.base <= span.start)
+            .checked_sub(1)?;
+
+        let file = &self.files[idx];
+        if file
+            .span()
+            .contains(span.with_end(span.end.max(span.start)))
+        {
+            Some(file)
+        } else {
+            None
+        }
+    }
+
+    /// Resolve the global span of [item] to a file, locations and slice
+    pub fn resolve<T: WithSpan>(&self, item: &T) -> Option<ResolvedSpan<'_>> {
+        let global = item.span();
+        let file = self.lookup(global)?;
+        let span = file.local(global);
+
+        Some(ResolvedSpan {
+            file: file.id,
+            name: &file.name,
+            span,
+            start: file.index.location(span.start)?,
+            end: file.index.location(span.end.max(span.start))?,
+            slice: file.source.get(span.range()).unwrap_or_default(),
+        })
+    }
+
+    /// Render a diagnostic with global spans
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        Renderer::from_map(self).render(diagnostic)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Input, Label};
+
+    #[test]
+    fn resolve() {
+        let mut map = SourceMap::new();
+        let a = map.add("a.txt", "hello\nworld");
+        let b = map.add("b.txt", "foo bar");
+
+        let span = map.span(b, Span::new(4, 7));
+        let resolved = map.resolve(&span).unwrap();
+
+        assert_eq!(resolved.file, b);
+        assert_eq!(resolved.name, "b.txt");
+        assert_eq!(resolved.slice, "bar");
+        assert_eq!(resolved.start, Location { line: 0, column: 4 });
+
+        let span = map.span(a, Span::new(6, 11));
+        let resolved = map.resolve(&span).unwrap();
+        assert_eq!(resolved.file, a);
+        assert_eq!(resolved.slice, "world");
+        assert_eq!(resolved.start, Location { line: 1, column: 0 });
+
+        // End of a file
+        let span = map.span(a, Span::new(11, 11));
+        assert_eq!(map.resolve(&span).unwrap().file, a);
+    }
+
+    #[test]
+    fn render_files() {
+        let mut map = SourceMap::new();
+        let a = map.add("a.txt", "let x = y;");
+        let b = map.add("b.txt", "\nlet y = [1 2];");
+
+        let mut input = Input::new(map.file(b).unwrap().source());
+        let error = input.parse(("\nlet y = [1", ',', " 2];")).unwrap_err();
+
+        let diagnostic = Diagnostic::from(&error)
+            .map_spans(|span| map.span(b, span))
+            .with_label(Label::secondary(map.span(a, Span::new(8, 9))).with_message("used here"));
+
+        assert_eq!(
+            map.render(&diagnostic),
+            "\
+error: expected `,`, found ' '
+ --> b.txt:2:11
+  |
+2 | let y = [1 2];
+  |           ^ unexpected ' '
+  |
+ ::: a.txt:1:9
+  |
+1 | let x = y;
+  |         - used here
+"
+        );
+    }
+}
@@ -82,6 +82,10 @@ impl Span {
         }
     }
 
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub const fn range(&self) -> core::ops::Range<usize> {
         self.start..self.end
     }
@@ -0,0 +1,192 @@
+use alloc::vec::Vec;
+
+use crate::{memo::Memo, Error, ErrorKind, Limit};
+
+/// State shared by all readers of a single parse
+#[derive(Default)]
+pub(crate) struct State {
+    /// The furthest expected-failure seen so far
+    furthest: Option<Error>,
+    /// A cleared furthest failure, reused by the next failure
+    spare: Option<Error>,
+    /// When non-zero, failures are not tracked
+    silent: usize,
+    /// Errors reported by recovering tokenizers
+    diagnostics: Vec<Error>,
+    /// Results of memoized rules
+    memo: Memo,
+    pub max_depth: Option<usize>,
+    pub max_steps: Option<usize>,
+    depth: usize,
+    steps: usize,
+    /// The first exceeded limit and where. Once set, all tokenizers fail
+    exceeded: Option<(usize, Limit)>,
+    #[cfg(feature = "trace")]
+    pub tracer: Option<alloc::boxed::Box<dyn crate::Trace>>,
+}
+
+impl State {
+    pub fn fail(&mut self, error: &Error) {
+        if self.silent > 0 || !matches!(error.kind(), ErrorKind::Expected { .. }) {
+            return;
+        }
+
+        let furthest = match self.furthest.take() {
+            Some(furthest) if furthest.position() > error.position() => furthest,
+            Some(furthest) if furthest.position() == error.position() => {
+                let Some(error) = error.clone_expected() else {
+                    return;
+                };
+                furthest.merge(error)
+            }
+            // Reuse the allocations of a tracked error, so tracking does not allocate in a hot loop
+            Some(mut furthest) => {
+                furthest.clone_expected_from(error);
+                furthest
+            }
+            None => match self.spare.take() {
+                Some(mut spare) => {
+                    spare.clone_expected_from(error);
+                    spare
+                }
+                None => {
+                    let Some(error) = error.clone_expected() else {
+                        return;
+                    };
+                    error
+                }
+            },
+        };
+
+        self.furthest = Some(furthest);
+    }
+
+    /// Stop tracking the furthest failure, keeping it to reuse its allocations
+    fn clear_furthest(&mut self) {
+        if let Some(furthest) = self.furthest.take() {
+            self.spare = Some(furthest);
+        }
+    }
+
+    /// Replace the failures tracked at the position of [error] with [error]
+    pub fn relabel(&mut self, error: &Error) {
+        if self.silent > 0 {
+            return;
+        }
+
+        if let Some(furthest) = &self.furthest {
+            if furthest.position() > error.position() {
+                return;
+            }
+        }
+
+        self.furthest = error.clone_expected();
+    }
+
+    /// Enter a tokenizer at [position], counting depth and steps
+    pub fn enter(&mut self, position: usize) -> Result<(), Error> {
+        if let Some((position, limit)) = self.exceeded {
+            return Err(Error::limit(position, limit));
+        }
+
+        self.steps += 1;
+
+        let limit = match (self.max_depth, self.max_steps) {
+            (Some(max), _) if self.depth >= max => Limit::Depth(max),
+            (_, Some(max)) if self.steps > max => Limit::Steps(max),
+            _ => {
+                self.depth += 1;
+                return Ok(());
+            }
+        };
+
+        self.exceeded = Some((position, limit));
+        Err(Error::limit(position, limit))
+    }
+
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    pub fn enter_silent(&mut self) {
+        self.silent += 1;
+    }
+
+    pub fn leave_silent(&mut self) {
+        self.silent -= 1;
+    }
+
+    pub fn memo(&self) -> &Memo {
+        &self.memo
+    }
+
+    pub fn memo_mut(&mut self) -> &mut Memo {
+        &mut self.memo
+    }
+
+    /// Combine [error] with the furthest failure and reset the tracking
+    pub fn finish(&mut self, error: Error) -> Error {
+        self.memo.clear();
+        self.steps = 0;
+
+        if let Some((position, limit)) = self.exceeded.take() {
+            self.clear_furthest();
+            return Error::limit(position, limit);
+        }
+
+        let Some(furthest) = self.furthest.take() else {
+            return error;
+        };
+
+        match error.kind() {
+            ErrorKind::Expected { .. } => error.merge(furthest),
+            _ => error,
+        }
+    }
+
+    pub fn report(&mut self, error: Error) {
+        // Add the items of alternatives that failed at the same position
+        let error = match self.furthest.take() {
+            Some(furthest)
+                if furthest.position() == error.position()
+                    && matches!(error.kind(), ErrorKind::Expected { .. }) =>
+            {
+                error.merge(furthest)
+            }
+            furthest => {
+                self.furthest = furthest;
+                error
+            }
+        };
+
+        self.diagnostics.push(error);
+        // Failures inside the recovered input are already reported
+        self.clear_furthest();
+    }
+
+    pub fn diagnostics(&self) -> &[Error] {
+        &self.diagnostics
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<Error> {
+        core::mem::take(&mut self.diagnostics)
+    }
+
+    /// Drop diagnostics reported after [mark], when backtracking
+    pub fn truncate_diagnostics(&mut self, mark: usize) {
+        self.diagnostics.truncate(mark);
+    }
+
+    /// The exceeded limit, if any, as an error
+    pub fn exceeded(&self) -> Option<Error> {
+        self.exceeded
+            .map(|(position, limit)| Error::limit(position, limit))
+    }
+
+    pub fn reset(&mut self) {
+        self.clear_furthest();
+        self.memo.clear();
+        self.steps = 0;
+        self.exceeded = None;
+    }
+}
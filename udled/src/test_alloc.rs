@@ -0,0 +1,37 @@
+//! A global allocator counting the allocations of each test thread
+
+extern crate std;
+
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::Cell,
+};
+
+use std::alloc::System;
+
+std::thread_local! {
+    static COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = COUNT.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// The number of allocations made by [func]
+pub fn allocations<F: FnOnce()>(func: F) -> usize {
+    let start = COUNT.with(Cell::get);
+    func();
+    COUNT.with(Cell::get) - start
+}
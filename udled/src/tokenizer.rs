@@ -1,7 +1,11 @@
-use alloc::{format, string::ToString};
-
 use crate::{
-    buffer::Buffer, error::Error, item::Item, reader::Reader, span::Span, AsBytes, AsChar, Either,
+    buffer::Buffer,
+    error::{Error, Expected, Text},
+    item::Item,
+    reader::Reader,
+    span::Span,
+    tokenizers::Sequence,
+    AsBytes, AsChar, Either,
 };
 
 pub trait Tokenizer<'input, B: Buffer<'input>> {
@@ -14,12 +18,21 @@ pub trait Tokenizer<'input, B: Buffer<'input>> {
         Ok(())
     }
 
+    /// Predict whether the tokenizer matches at the current position.
+    /// Sequences predict from their first token only, so a sequence can fail after
+    /// its peek succeeds. Use [Lookahead](crate::tokenizers::Lookahead) to peek more
+    /// tokens, or [Peek](crate::tokenizers::Peek) to match all of them
     fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
         self.eat(reader).is_ok()
     }
+
+    /// A readable name of the tokenizer, used when tracing
+    fn name(&self) -> Option<&str> {
+        None
+    }
 }
 
-impl<'a, 'input, B, T> Tokenizer<'input, B> for &'a T
+impl<'input, B, T> Tokenizer<'input, B> for &T
 where
     B: Buffer<'input>,
     T: Tokenizer<'input, B>,
@@ -37,6 +50,10 @@ where
     fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
         (**self).to_token(reader)
     }
+
+    fn name(&self) -> Option<&str> {
+        (**self).name()
+    }
 }
 
 /// Match a literal char
@@ -47,17 +64,15 @@ where
 {
     type Token = Item<char>;
     fn to_token(&self, reader: &mut Reader<'_, 'input, S>) -> Result<Self::Token, Error> {
-        let next = reader.parse(Char)?;
-        if &next.value == self {
-            Ok(next)
-        } else {
-            Err(reader.error(format!("{}", self)))
+        match reader.peek_ch().and_then(|m| m.as_char()) {
+            Some(next) if next == *self => reader.parse(Char),
+            _ => Err(reader.expected(*self)),
         }
     }
 }
 
 /// Match a literal string
-impl<'lit, 'input, B> Tokenizer<'lit, B> for &'input str
+impl<'lit, B> Tokenizer<'lit, B> for &str
 where
     B: Buffer<'lit>,
     B::Item: AsChar,
@@ -65,21 +80,14 @@ where
 {
     type Token = Item<&'lit str>;
     fn to_token(&self, reader: &mut Reader<'_, 'lit, B>) -> Result<Self::Token, Error> {
-        let tokens = self.chars();
+        if self.is_empty() || !self.peek(reader) {
+            return Err(reader.expected(Expected::Literal(Text::new(self))));
+        }
 
         let start = reader.position();
 
-        for token in tokens {
-            let Some(next) = reader.read()?.as_char() else {
-                return Err(reader.error(self.to_string()));
-            };
-            if token != next {
-                return Err(reader.error(self.to_string()));
-            }
-        }
-
-        if start == reader.position() {
-            return Err(reader.error(self.to_string()));
+        for _ in self.chars() {
+            reader.read()?;
         }
 
         let span = Span {
@@ -122,12 +130,19 @@ where
 
     fn to_token(&self, reader: &mut Reader<'_, 'input, S>) -> Result<Self::Token, Error> {
         let start = reader.position();
-        match reader.read()?.as_char() {
-            Some(ret) => Ok(Item {
-                span: Span::new(start, start + ret.len_utf8()),
-                value: ret,
-            }),
-            None => Err(reader.error("char")),
+        let Some(item) = reader.peek_ch() else {
+            return Err(reader.expected(Expected::Class("char")));
+        };
+
+        match item.as_char() {
+            Some(ret) => {
+                reader.read()?;
+                Ok(Item {
+                    span: Span::new(start, start + ret.len_utf8()),
+                    value: ret,
+                })
+            }
+            None => Err(reader.expected(Expected::Class("char"))),
         }
     }
 }
@@ -140,12 +155,18 @@ where
     type Token = Item<char>;
 
     fn to_token<'a>(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
-        let char = reader.parse(Char)?;
-
-        if !self.contains(&char.value) {
-            return Err(reader.error(format!("Expected char in range: {:?}", self)));
+        match reader.peek_ch().and_then(|m| m.as_char()) {
+            Some(next) if self.contains(&next) => reader.parse(Char),
+            _ => {
+                // The last char in the range, skipping back over the surrogates
+                let end = match self.end {
+                    '\0' => '\0',
+                    '\u{E000}' => '\u{D7FF}',
+                    end => char::from_u32(u32::from(end) - 1).unwrap_or(end),
+                };
+                Err(reader.expected(Expected::Range(self.start, end)))
+            }
         }
-        Ok(char)
     }
 }
 
@@ -157,13 +178,10 @@ where
     type Token = Item<char>;
 
     fn to_token<'a>(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
-        let char = reader.parse(Char)?;
-
-        if !self.contains(&char.value) {
-            return Err(reader.error(format!("Expected char in range: {:?}", self)));
+        match reader.peek_ch().and_then(|m| m.as_char()) {
+            Some(next) if self.contains(&next) => reader.parse(Char),
+            _ => Err(reader.expected(Expected::Range(*self.start(), *self.end()))),
         }
-
-        Ok(char)
     }
 }
 
@@ -209,10 +227,36 @@ where
 
     fn to_token(&self, reader: &mut Reader<'_, 'input, S>) -> Result<Self::Token, Error> {
         if reader.peek_ch().is_some() {
-            return Err(reader.error("EOF"));
+            return Err(reader.expected(Expected::Eof));
         }
         Ok(reader.position())
     }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, S>) -> bool {
+        reader.peek_ch().is_none()
+    }
+}
+
+/// Eat [tokenizer] if more tokens are left to peek after it, otherwise peek it.
+/// Returns the result once it is known
+fn peek_step<'input, T, B>(
+    reader: &mut Reader<'_, 'input, B>,
+    tokenizer: &T,
+    n: &mut usize,
+) -> Option<bool>
+where
+    T: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    match *n {
+        0 => Some(true),
+        1 => Some(reader.is(tokenizer)),
+        _ if reader.eat(tokenizer).is_err() => Some(false),
+        _ => {
+            *n -= 1;
+            None
+        }
+    }
 }
 
 macro_rules! tuples {
@@ -236,6 +280,16 @@ macro_rules! tuples {
                 self.0.eat(reader)
             }
         }
+
+        impl<'input, $first, B> Sequence<'input, B> for ($first,)
+        where
+            B: Buffer<'input>,
+            $first: Tokenizer<'input, B>,
+        {
+            fn peek_n(&self, reader: &mut Reader<'_, 'input, B>, n: usize) -> bool {
+                n == 0 || reader.is(&self.0)
+            }
+        }
     };
     ($first: ident, $($rest:ident),+) => {
 
@@ -276,7 +330,50 @@ macro_rules! tuples {
                 Ok(())
             }
         }
+
+        #[allow(non_snake_case)]
+        impl<'input, $first, $($rest),+, B> Sequence<'input, B> for ($first, $($rest),+)
+        where
+            B: Buffer<'input>,
+            $first: Tokenizer<'input, B>,
+            $(
+                $rest: Tokenizer<'input, B>
+            ),+
+        {
+            fn peek_n(&self, reader: &mut Reader<'_, 'input, B>, n: usize) -> bool {
+                let ($first, $($rest),+) = self;
+                let mut n = n;
+                if let Some(ret) = peek_step(reader, $first, &mut n) {
+                    return ret;
+                }
+                $(
+                    if let Some(ret) = peek_step(reader, $rest, &mut n) {
+                        return ret;
+                    }
+                )+
+                true
+            }
+        }
     };
 }
 
 tuples!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+
+#[cfg(test)]
+mod test {
+    use crate::{Expected, Input};
+
+    #[test]
+    fn char_range_end() {
+        let mut input = Input::new("x");
+
+        let err = input.parse('a'..'e').unwrap_err();
+        assert_eq!(err.expected_items(), &[Expected::Range('a', 'd')]);
+
+        let err = input.parse('\u{D000}'..'\u{E000}').unwrap_err();
+        assert_eq!(
+            err.expected_items(),
+            &[Expected::Range('\u{D000}', '\u{D7FF}')]
+        );
+    }
+}
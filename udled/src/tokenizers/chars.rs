@@ -1,7 +1,7 @@
-use crate::{AsChar, Buffer, Char, Item, StringExt, Tokenizer};
+use crate::{AsChar, Buffer, Char, Expected, Item, StringExt, Tokenizer};
 
 macro_rules! impls {
-    ($($name: ident => $method: ident),+) => {
+    ($($name: ident => $method: ident, $desc: literal),+) => {
       $(
         #[derive(Debug, Clone, Copy)]
         pub struct $name;
@@ -17,12 +17,10 @@ macro_rules! impls {
                 &self,
                 reader: &mut crate::Reader<'_, 'input, B>,
             ) -> Result<Self::Token, crate::Error> {
-                let char = reader.parse(Char)?;
-                if !char.value.$method() {
-                    return Err(reader.error(stringify!($name)));
+                match reader.peek_ch().and_then(|m| m.as_char()) {
+                    Some(next) if next.$method() => reader.parse(Char),
+                    _ => Err(reader.expected(Expected::Class($desc))),
                 }
-
-                Ok(char)
             }
         }
       )+
@@ -30,11 +28,11 @@ macro_rules! impls {
 }
 
 impls!(
-  Alphabetic => is_alphabetic,
-  AlphaNumeric => is_alphanumeric,
-  Punct => is_ascii_punctuation,
-  Numeric => is_numeric,
-  AsciiWhiteSpace => is_ascii_whitespace,
-  WhiteSpace => is_whitespace,
-  LineBreak => is_linebreak
+  Alphabetic => is_alphabetic, "alphabetic char",
+  AlphaNumeric => is_alphanumeric, "alphanumeric char",
+  Punct => is_ascii_punctuation, "punctuation",
+  Numeric => is_numeric, "numeric char",
+  AsciiWhiteSpace => is_ascii_whitespace, "ascii whitespace",
+  WhiteSpace => is_whitespace, "whitespace",
+  LineBreak => is_linebreak, "line break"
 );
@@ -0,0 +1,126 @@
+use core::marker::PhantomData;
+
+use alloc::fmt;
+
+use crate::{Buffer, Error, Reader, Tokenizer};
+
+pub const fn cut<T, B>(tokenizer: T) -> Cut<T, B> {
+    Cut::new(tokenizer)
+}
+
+/// Commit to the current branch.
+///
+/// Failures of [T] are marked as committed, so [Or](crate::tokenizers::Or)
+/// does not try the next alternative, and optionals and repetitions
+/// return the error instead of stopping.
+/// Place it after an unambiguous prefix: `("if", cut((ws, expr, block)))`
+pub struct Cut<T, B> {
+    tokenizer: T,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, B> Cut<T, B> {
+    pub const fn new(tokenizer: T) -> Cut<T, B> {
+        Cut {
+            tokenizer,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, B> Clone for Cut<T, B> {
+    fn clone(&self) -> Self {
+        Cut {
+            tokenizer: self.tokenizer.clone(),
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, B> Copy for Cut<T, B> {}
+
+impl<T: fmt::Debug, B> fmt::Debug for Cut<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Cut")
+            .field("tokenizer", &self.tokenizer)
+            .finish()
+    }
+}
+
+impl<'input, T, B> Tokenizer<'input, B> for Cut<T, B>
+where
+    T: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = T::Token;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        self.tokenizer.to_token(reader).map_err(Error::committed)
+    }
+
+    fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
+        self.tokenizer.eat(reader).map_err(Error::committed)
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.tokenizer.peek(reader)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use crate::{
+        tokenizers::{Digit, Puntuated},
+        Input, TokenizerExt,
+    };
+
+    use super::*;
+
+    #[test]
+    fn or() {
+        let mut input = Input::new("let =");
+
+        // Without the cut, the right branch matches
+        assert!(input.is(("let ", 'x').or("let")));
+
+        let err = input.parse(("let ", cut('x')).or("let")).unwrap_err();
+        assert!(err.is_committed());
+        assert_eq!(err.to_string(), "@4: expected `x`, found `=`");
+    }
+
+    #[test]
+    fn many() {
+        let mut input = Input::new("a1a2ab");
+
+        let err = input.parse(('a', Digit(10).cut()).many()).unwrap_err();
+        assert_eq!(err.position(), 5);
+
+        let ret = input.parse(('a', Digit(10)).many()).unwrap();
+        assert_eq!(ret.value.len(), 2);
+    }
+
+    #[test]
+    fn optional() {
+        let mut input = Input::new("-x");
+
+        let err = input
+            .parse((('-', cut(Digit(10))).optional(), 'x'))
+            .unwrap_err();
+        assert_eq!(err.position(), 1);
+    }
+
+    #[test]
+    fn punctuated() {
+        let mut input = Input::new("1,2,x");
+
+        let ret = input.parse(Puntuated::new(Digit(10), ',')).unwrap();
+        assert_eq!(ret.len(), 3);
+
+        // An item is required after a separator
+        let mut input = Input::new("1,2,x");
+        let err = input.parse(Digit(10).cut().punctuated(',')).unwrap_err();
+        assert_eq!(err.position(), 4);
+    }
+}
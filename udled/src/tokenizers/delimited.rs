@@ -0,0 +1,186 @@
+use core::marker::PhantomData;
+
+use alloc::{fmt, format};
+
+use crate::{Buffer, Error, Item, Reader, Span, Tokenizer};
+
+pub const fn delimited<O, T, C, B>(open: O, inner: T, close: C) -> Delimited<O, T, C, B> {
+    Delimited::new(open, inner, close)
+}
+
+/// Matches [T] between [O] and [C], like `[1, 2]`.
+///
+/// If [C] fails after [O] matched, the error gets a label pointing at the
+/// opening delimiter: "unclosed `[` opened here". With nested delimiters
+/// the innermost unclosed one is reported
+pub struct Delimited<O, T, C, B> {
+    open: O,
+    inner: T,
+    close: C,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<O, T, C, B> Delimited<O, T, C, B> {
+    pub const fn new(open: O, inner: T, close: C) -> Delimited<O, T, C, B> {
+        Delimited {
+            open,
+            inner,
+            close,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<O: Clone, T: Clone, C: Clone, B> Clone for Delimited<O, T, C, B> {
+    fn clone(&self) -> Self {
+        Delimited {
+            open: self.open.clone(),
+            inner: self.inner.clone(),
+            close: self.close.clone(),
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<O: Copy, T: Copy, C: Copy, B> Copy for Delimited<O, T, C, B> {}
+
+impl<O: fmt::Debug, T: fmt::Debug, C: fmt::Debug, B> fmt::Debug for Delimited<O, T, C, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Delimited")
+            .field("open", &self.open)
+            .field("inner", &self.inner)
+            .field("close", &self.close)
+            .finish()
+    }
+}
+
+impl<'input, O, T, C, B> Tokenizer<'input, B> for Delimited<O, T, C, B>
+where
+    O: Tokenizer<'input, B>,
+    T: Tokenizer<'input, B>,
+    C: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = Item<T::Token>;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        // Skip trivia first, so the unclosed label points at the opener itself
+        reader.skip()?;
+        let start = reader.position();
+        let checkpoint = reader.checkpoint();
+        reader.eat(&self.open)?;
+        let open = Span::new(start, reader.position());
+        let opener = reader.describe_since(&checkpoint);
+
+        let unclosed = |reader: &mut Reader<'_, 'input, B>, err: Error| {
+            let message = match &opener {
+                Some(opener) => format!("unclosed {opener} opened here"),
+                None => "unclosed delimiter opened here".into(),
+            };
+            let err = err.with_label(open, message);
+            // The label is kept if this is the furthest failure
+            reader.track(&err);
+            err
+        };
+
+        let inner = match reader.parse(&self.inner) {
+            Ok(inner) => inner,
+            // Only a missing closer is reported at the opener
+            Err(err) if !reader.is(&self.close) && err.labels().next().is_none() => {
+                return Err(unclosed(reader, err))
+            }
+            Err(err) => return Err(err),
+        };
+
+        if let Err(err) = reader.eat(&self.close) {
+            return Err(unclosed(reader, err));
+        }
+
+        Ok(Item::new(Span::new(start, reader.position()), inner))
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.open.peek(reader)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use crate::{
+        tokenizers::{Digit, Puntuated, WhiteSpace},
+        Diagnostic, Input, IntoTokenizer, Reader, Result, TokenizerExt,
+    };
+
+    use super::*;
+
+    #[test]
+    fn unclosed() {
+        let mut input = Input::new("[1,2}");
+
+        let err = input
+            .parse(Puntuated::new(Digit(10), ',').delimited('[', ']'))
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "@4: expected one of `]`, `,` but found `}`"
+        );
+        assert_eq!(
+            err.labels().collect::<alloc::vec::Vec<_>>(),
+            [(Span::new(0, 1), "unclosed `[` opened here")]
+        );
+    }
+
+    fn list<'input, B>(reader: &mut Reader<'_, 'input, B>) -> Result<()>
+    where
+        B: Buffer<'input>,
+        B::Item: crate::AsChar,
+    {
+        reader.eat(
+            Digit(10)
+                .or(list.into_tokenizer())
+                .many0()
+                .delimited('[', ']'),
+        )
+    }
+
+    #[test]
+    fn nested() {
+        let source = "[1[2[3]4]";
+        let mut input = Input::new(source);
+
+        let err = input.eat(list.into_tokenizer()).unwrap_err();
+        assert_eq!(err.position(), 9);
+        assert_eq!(err.labels().next().unwrap().0, Span::new(0, 1));
+
+        let source = "[1[2}]";
+        let mut input = Input::new(source);
+
+        let err = input.eat(list.into_tokenizer()).unwrap_err();
+        assert_eq!(err.labels().next().unwrap().0, Span::new(2, 3));
+
+        let rendered = Diagnostic::from(&err).render(source);
+        assert!(rendered.contains("unclosed `[` opened here"));
+    }
+
+    #[test]
+    fn words() {
+        let mut input = Input::new("  begin 1 2 stop");
+
+        let err = input
+            .parse(
+                Digit(10)
+                    .many0()
+                    .delimited("begin", "end")
+                    .skipping(WhiteSpace),
+            )
+            .unwrap_err();
+
+        assert_eq!(
+            err.labels().collect::<alloc::vec::Vec<_>>(),
+            [(Span::new(2, 7), "unclosed `begin` opened here")]
+        );
+    }
+}
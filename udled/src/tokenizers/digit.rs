@@ -1,6 +1,6 @@
 use alloc::vec::Vec;
 
-use crate::{AsChar, Buffer, Char, Error, Item, Reader, Tokenizer};
+use crate::{AsChar, Buffer, Char, Error, Expected, Item, Reader, Tokenizer};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Digit(pub u32);
@@ -25,6 +25,10 @@ where
     type Token = Item<u32>;
 
     fn to_token(&self, reader: &mut Reader<'_, 'input, S>) -> Result<Self::Token, Error> {
+        if !self.peek(reader) {
+            return Err(reader.expected(Expected::Class("digit")));
+        }
+
         let item = reader.parse(Char)?;
 
         item.value
@@ -2,7 +2,7 @@ use core::marker::PhantomData;
 
 use alloc::fmt;
 
-use crate::{tokenizers::next::Next, Buffer, Tokenizer};
+use crate::{tokenizers::next::Next, Buffer, Expected, Tokenizer};
 
 /// Matches everything but [T]
 pub struct Exclude<T, B> {
@@ -49,14 +49,14 @@ where
         &self,
         reader: &mut crate::Reader<'_, 'input, B>,
     ) -> Result<Self::Token, crate::Error> {
-        if reader.is(&self.tokenizer) {
-            return Err(reader.error("unexpected"));
+        if reader.is_silent(&self.tokenizer) {
+            return Err(reader.expected(Expected::Class("something else")));
         }
 
         reader.parse(Next)
     }
 
     fn peek(&self, reader: &mut crate::Reader<'_, 'input, B>) -> bool {
-        !self.tokenizer.peek(reader)
+        !reader.is_silent(&self.tokenizer)
     }
 }
@@ -0,0 +1,509 @@
+use core::marker::PhantomData;
+
+use alloc::{boxed::Box, fmt, vec::Vec};
+
+use crate::{Buffer, Error, Expected, Reader, Span, Text, Tokenizer};
+
+/// Associativity of an infix operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`
+    Right,
+}
+
+struct Operand<T> {
+    span: Span,
+    value: T,
+}
+
+/// Parse an operand with a minimum binding power
+type Next<'r, 'input, B, T> =
+    &'r dyn Fn(&mut Reader<'_, 'input, B>, u16) -> Result<Operand<T>, Error>;
+
+trait Op<'input, B: Buffer<'input>> {
+    fn matches(&self, reader: &mut Reader<'_, 'input, B>) -> bool;
+}
+
+trait PrefixOp<'input, B: Buffer<'input>, T>: Op<'input, B> {
+    fn apply(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        power: u16,
+        next: Next<'_, 'input, B, T>,
+    ) -> Result<Operand<T>, Error>;
+}
+
+trait InfixOp<'input, B: Buffer<'input>, T>: Op<'input, B> {
+    fn apply(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        lhs: Operand<T>,
+        power: u16,
+        next: Next<'_, 'input, B, T>,
+    ) -> Result<Operand<T>, Error>;
+}
+
+trait PostfixOp<'input, B: Buffer<'input>, T>: Op<'input, B> {
+    fn apply(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        lhs: Operand<T>,
+    ) -> Result<Operand<T>, Error>;
+}
+
+struct Operator<O, F> {
+    op: O,
+    func: F,
+}
+
+impl<'input, B, O, F> Op<'input, B> for Operator<O, F>
+where
+    B: Buffer<'input>,
+    O: Tokenizer<'input, B>,
+{
+    fn matches(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        reader.is(&self.op)
+    }
+}
+
+impl<'input, B, T, O, F> PrefixOp<'input, B, T> for Operator<O, F>
+where
+    B: Buffer<'input>,
+    O: Tokenizer<'input, B>,
+    F: Fn(Span, O::Token, T) -> T,
+{
+    fn apply(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        power: u16,
+        next: Next<'_, 'input, B, T>,
+    ) -> Result<Operand<T>, Error> {
+        let start = reader.position();
+        let token = reader.parse(&self.op)?;
+        let rhs = next(reader, power)?;
+        let span = Span::new(start, rhs.span.end);
+
+        Ok(Operand {
+            span,
+            value: (self.func)(span, token, rhs.value),
+        })
+    }
+}
+
+impl<'input, B, T, O, F> InfixOp<'input, B, T> for Operator<O, F>
+where
+    B: Buffer<'input>,
+    O: Tokenizer<'input, B>,
+    F: Fn(Span, T, O::Token, T) -> T,
+{
+    fn apply(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        lhs: Operand<T>,
+        power: u16,
+        next: Next<'_, 'input, B, T>,
+    ) -> Result<Operand<T>, Error> {
+        let token = reader.parse(&self.op)?;
+        let rhs = next(reader, power)?;
+        let span = Span::new(lhs.span.start, rhs.span.end);
+
+        Ok(Operand {
+            span,
+            value: (self.func)(span, lhs.value, token, rhs.value),
+        })
+    }
+}
+
+impl<'input, B, T, O, F> PostfixOp<'input, B, T> for Operator<O, F>
+where
+    B: Buffer<'input>,
+    O: Tokenizer<'input, B>,
+    F: Fn(Span, T, O::Token) -> T,
+{
+    fn apply(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        lhs: Operand<T>,
+    ) -> Result<Operand<T>, Error> {
+        let token = reader.parse(&self.op)?;
+        let span = Span::new(lhs.span.start, reader.position());
+
+        Ok(Operand {
+            span,
+            value: (self.func)(span, lhs.value, token),
+        })
+    }
+}
+
+struct Ternary<O, S, F> {
+    first: O,
+    second: S,
+    func: F,
+}
+
+impl<'input, B, O, S, F> Op<'input, B> for Ternary<O, S, F>
+where
+    B: Buffer<'input>,
+    O: Tokenizer<'input, B>,
+{
+    fn matches(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        reader.is(&self.first)
+    }
+}
+
+impl<'input, B, T, O, S, F> InfixOp<'input, B, T> for Ternary<O, S, F>
+where
+    B: Buffer<'input>,
+    O: Tokenizer<'input, B>,
+    S: Tokenizer<'input, B>,
+    F: Fn(Span, T, T, T) -> T,
+{
+    fn apply(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        lhs: Operand<T>,
+        power: u16,
+        next: Next<'_, 'input, B, T>,
+    ) -> Result<Operand<T>, Error> {
+        reader.eat(&self.first)?;
+        let middle = next(reader, 0)?;
+        reader.eat(&self.second)?;
+        let rhs = next(reader, power)?;
+        let span = Span::new(lhs.span.start, rhs.span.end);
+
+        Ok(Operand {
+            span,
+            value: (self.func)(span, lhs.value, middle.value, rhs.value),
+        })
+    }
+}
+
+struct Binding<O> {
+    /// Binding power to the left. Unused for prefix operators
+    left: u16,
+    /// Binding power to the right. Unused for postfix operators
+    right: u16,
+    op: O,
+}
+
+/// An expression parser using precedence climbing (Pratt parsing).
+///
+/// Operands are parsed with [A], and operators are combined with callbacks,
+/// which get the span of the whole operation. Operators with a higher precedence
+/// bind tighter. When several operators of a kind match, the first one added wins,
+/// so add `**` before `*`.
+///
+/// To parse parenthesized expressions, let the atom refer back to a function
+/// building the expression.
+pub struct Expression<'a, 'input, B, A, T>
+where
+    B: Buffer<'input>,
+{
+    atom: A,
+    name: Text,
+    prefix: Vec<Binding<Box<dyn PrefixOp<'input, B, T> + 'a>>>,
+    infix: Vec<Binding<Box<dyn InfixOp<'input, B, T> + 'a>>>,
+    postfix: Vec<Binding<Box<dyn PostfixOp<'input, B, T> + 'a>>>,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<'a, 'input, B, A, T> Expression<'a, 'input, B, A, T>
+where
+    B: Buffer<'input>,
+    A: Tokenizer<'input, B, Token = T>,
+{
+    pub fn new(atom: A) -> Expression<'a, 'input, B, A, T> {
+        Expression {
+            atom,
+            name: Text::from("expression"),
+            prefix: Vec::new(),
+            infix: Vec::new(),
+            postfix: Vec::new(),
+            buffer: PhantomData,
+        }
+    }
+
+    /// Name used in errors when an operand is missing. Defaults to "expression"
+    pub fn with_name(mut self, name: impl Into<Text>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Add a prefix operator, like `-a`
+    pub fn prefix<O, F>(mut self, precedence: u8, op: O, func: F) -> Self
+    where
+        O: Tokenizer<'input, B> + 'a,
+        F: Fn(Span, O::Token, T) -> T + 'a,
+    {
+        let (_, right) = powers(precedence, Assoc::Left);
+        self.prefix.push(Binding {
+            left: 0,
+            right,
+            op: Box::new(Operator { op, func }),
+        });
+        self
+    }
+
+    /// Add an infix operator, like `a + b`
+    pub fn infix<O, F>(mut self, precedence: u8, assoc: Assoc, op: O, func: F) -> Self
+    where
+        O: Tokenizer<'input, B> + 'a,
+        F: Fn(Span, T, O::Token, T) -> T + 'a,
+    {
+        let (left, right) = powers(precedence, assoc);
+        self.infix.push(Binding {
+            left,
+            right,
+            op: Box::new(Operator { op, func }),
+        });
+        self
+    }
+
+    /// Add a postfix operator, like `a!`
+    pub fn postfix<O, F>(mut self, precedence: u8, op: O, func: F) -> Self
+    where
+        O: Tokenizer<'input, B> + 'a,
+        F: Fn(Span, T, O::Token) -> T + 'a,
+    {
+        let (left, _) = powers(precedence, Assoc::Left);
+        self.postfix.push(Binding {
+            left,
+            right: 0,
+            op: Box::new(Operator { op, func }),
+        });
+        self
+    }
+
+    /// Add a right associative ternary operator, like `a ? b : c`
+    pub fn ternary<O, S, F>(mut self, precedence: u8, first: O, second: S, func: F) -> Self
+    where
+        O: Tokenizer<'input, B> + 'a,
+        S: Tokenizer<'input, B> + 'a,
+        F: Fn(Span, T, T, T) -> T + 'a,
+    {
+        let (left, right) = powers(precedence, Assoc::Right);
+        self.infix.push(Binding {
+            left,
+            right,
+            op: Box::new(Ternary {
+                first,
+                second,
+                func,
+            }),
+        });
+        self
+    }
+
+    fn parse_operand(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        min: u16,
+    ) -> Result<Operand<T>, Error> {
+        let next = |reader: &mut Reader<'_, 'input, B>, min| self.parse_operand(reader, min);
+
+        let mut lhs = match self.prefix.iter().find(|m| m.op.matches(reader)) {
+            Some(prefix) => prefix.op.apply(reader, prefix.right, &next)?,
+            None => self.parse_atom(reader)?,
+        };
+
+        loop {
+            if let Some(postfix) = self.postfix.iter().find(|m| m.op.matches(reader)) {
+                if postfix.left < min {
+                    break;
+                }
+                lhs = postfix.op.apply(reader, lhs)?;
+                continue;
+            }
+
+            if let Some(infix) = self.infix.iter().find(|m| m.op.matches(reader)) {
+                if infix.left < min {
+                    break;
+                }
+                lhs = infix.op.apply(reader, lhs, infix.right, &next)?;
+                continue;
+            }
+
+            break;
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_atom(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Operand<T>, Error> {
+        // Skip trivia first, so the span covers only the operand
+        reader.skip()?;
+        let start = reader.position();
+
+        match reader.parse(&self.atom) {
+            Ok(value) => Ok(Operand {
+                span: Span::new(start, reader.position()),
+                value,
+            }),
+            Err(err) if err.position() == start && !err.is_committed() => {
+                Err(reader.expected_only(Expected::Named(self.name.clone())))
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Binding powers for a precedence. The lower power of the pair decides
+/// how operators of the same precedence group
+fn powers(precedence: u8, assoc: Assoc) -> (u16, u16) {
+    let power = (precedence as u16 + 1) * 2;
+    match assoc {
+        Assoc::Left => (power, power + 1),
+        Assoc::Right => (power + 1, power),
+    }
+}
+
+impl<'a, 'input, B, A, T> fmt::Debug for Expression<'a, 'input, B, A, T>
+where
+    B: Buffer<'input>,
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Expression")
+            .field("atom", &self.atom)
+            .field("name", &self.name)
+            .field("prefix", &self.prefix.len())
+            .field("infix", &self.infix.len())
+            .field("postfix", &self.postfix.len())
+            .finish()
+    }
+}
+
+impl<'a, 'input, B, A, T> Tokenizer<'input, B> for Expression<'a, 'input, B, A, T>
+where
+    B: Buffer<'input>,
+    A: Tokenizer<'input, B, Token = T>,
+{
+    type Token = T;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        self.parse_operand(reader, 0).map(|m| m.value)
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.prefix.iter().any(|m| m.op.matches(reader)) || reader.is(&self.atom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{
+        format,
+        string::{String, ToString},
+    };
+
+    use crate::{
+        tokenizers::{Digit, WhiteSpace},
+        AsChar, Input, IntoTokenizer, TokenizerExt, EOF,
+    };
+
+    use super::*;
+
+    fn atom<'input, B>(reader: &mut Reader<'_, 'input, B>) -> Result<String, Error>
+    where
+        B: Buffer<'input>,
+        B::Item: AsChar,
+    {
+        if reader.is('(') {
+            let (_, expr, _) = reader.parse(('(', expr.into_tokenizer(), ')'))?;
+            Ok(expr)
+        } else {
+            Ok(reader.parse(Digit(10))?.value.to_string())
+        }
+    }
+
+    fn expr<'input, B>(reader: &mut Reader<'_, 'input, B>) -> Result<String, Error>
+    where
+        B: Buffer<'input>,
+        B::Item: AsChar,
+    {
+        reader.parse(
+            Expression::new(atom.into_tokenizer())
+                .prefix(3, '-', |_, _, a| format!("(-{a})"))
+                .infix(1, Assoc::Left, '+', |_, a, _, b| format!("({a} + {b})"))
+                .infix(1, Assoc::Left, '-', |_, a, _, b| format!("({a} - {b})"))
+                .infix(2, Assoc::Left, '*', |_, a, _, b| format!("({a} * {b})"))
+                .infix(4, Assoc::Right, '^', |_, a, _, b| format!("({a} ^ {b})"))
+                .postfix(5, '!', |_, a, _| format!("({a}!)"))
+                .ternary(0, '?', ':', |_, a, b, c| format!("({a} ? {b} : {c})")),
+        )
+    }
+
+    fn parse(input: &str) -> Result<String, Error> {
+        Input::new(input)
+            .parse((expr.into_tokenizer(), EOF))
+            .map(|m| m.0)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(parse("1+2*3").unwrap(), "(1 + (2 * 3))");
+        assert_eq!(parse("1-2-3").unwrap(), "((1 - 2) - 3)");
+        assert_eq!(parse("2^3^4").unwrap(), "(2 ^ (3 ^ 4))");
+        assert_eq!(parse("-1*2").unwrap(), "((-1) * 2)");
+        assert_eq!(parse("-2^2").unwrap(), "(-(2 ^ 2))");
+        assert_eq!(parse("-3!").unwrap(), "(-(3!))");
+        assert_eq!(parse("(1+2)*3").unwrap(), "((1 + 2) * 3)");
+        assert_eq!(parse("1?2:3?4:5").unwrap(), "(1 ? 2 : (3 ? 4 : 5))");
+        assert_eq!(parse("1+2?3:4").unwrap(), "((1 + 2) ? 3 : 4)");
+    }
+
+    #[test]
+    fn spans() {
+        let mut input = Input::new("1+23*4");
+        let span = input
+            .parse(
+                Expression::new(Digit(10).many().spanned())
+                    .infix(1, Assoc::Left, '+', |span, _, _, _| span)
+                    .infix(2, Assoc::Left, '*', |span, a, _, _| {
+                        assert_eq!(a, Span::new(2, 4));
+                        span
+                    }),
+            )
+            .unwrap();
+
+        assert_eq!(span, Span::new(0, 6));
+    }
+
+    #[test]
+    fn missing_operand() {
+        let err = parse("1+").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "@2: expected expression, found end of input"
+        );
+
+        let err = parse("1*(2+)").unwrap_err();
+        assert_eq!(err.to_string(), "@5: expected expression, found `)`");
+
+        let err = parse("1?2").unwrap_err();
+        assert_eq!(err.position(), 3);
+    }
+
+    #[test]
+    fn skipping() {
+        let mut input = Input::new("1 +  23");
+        let span = input
+            .parse(
+                Expression::new(Digit(10).many().spanned())
+                    .infix(1, Assoc::Left, '+', |_, _, _, b| b)
+                    .skipping(WhiteSpace),
+            )
+            .unwrap();
+        assert_eq!(span, Span::new(5, 7));
+
+        let err = Input::new("1 + ")
+            .parse((expr.into_tokenizer(), EOF).skipping(WhiteSpace))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "@4: expected expression, found end of input"
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use alloc::string::ToString;
-
-use crate::{AsBytes, AsChar, Buffer, Item, Reader, Result, Span, Tokenizer};
+use crate::{
+    AsBytes, AsChar, Buffer, Error, Expected, Item, Reader, Result, Span, Text, Tokenizer,
+};
 
 pub struct IgnoreCase<T>(pub T);
 
@@ -13,37 +13,14 @@ where
 {
     type Token = Item<&'lit str>;
     fn to_token(&self, reader: &mut Reader<'_, 'lit, B>) -> Result<Self::Token> {
-        let tokens = self.0.as_ref().chars();
+        if self.0.as_ref().is_empty() || !self.peek(reader) {
+            return Err(self.error(reader));
+        }
 
         let start = reader.position();
 
-        for token in tokens {
-            let Some(next) = reader.read()?.as_char() else {
-                return Err(reader.error(self.0.as_ref().to_string()));
-            };
-
-            if token.is_ascii() && next.is_ascii() {
-                if !token.eq_ignore_ascii_case(&next) {
-                    return Err(reader.error(self.0.as_ref().to_string()));
-                }
-            } else {
-                let t = token.to_lowercase();
-                let n = next.to_lowercase();
-
-                if t.len() != n.len() {
-                    return Err(reader.error(self.0.as_ref().to_string()));
-                }
-
-                for (t, n) in t.zip(n) {
-                    if t != n {
-                        return Err(reader.error(self.0.as_ref().to_string()));
-                    }
-                }
-            }
-        }
-
-        if start == reader.position() {
-            return Err(reader.error(self.0.as_ref().to_string()));
+        for _ in self.0.as_ref().chars() {
+            reader.read()?;
         }
 
         let span = Span {
@@ -62,17 +39,35 @@ where
 
     fn peek(&self, reader: &mut Reader<'_, 'lit, B>) -> bool {
         let tokens = self.0.as_ref().chars();
-        for (idx, next) in tokens.enumerate() {
-            if Some(next) == reader.peek_chn(idx).and_then(|m| m.as_char()) {
-                continue;
+        for (idx, token) in tokens.enumerate() {
+            let Some(next) = reader.peek_chn(idx).and_then(|m| m.as_char()) else {
+                return false;
+            };
+
+            if token.is_ascii() && next.is_ascii() {
+                if !token.eq_ignore_ascii_case(&next) {
+                    return false;
+                }
+            } else {
+                let t = token.to_lowercase();
+                let n = next.to_lowercase();
+
+                if t.len() != n.len() || t.zip(n).any(|(t, n)| t != n) {
+                    return false;
+                }
             }
-            return false;
         }
 
         true
     }
 }
 
+impl<T: AsRef<str>> IgnoreCase<T> {
+    fn error<'lit, B: Buffer<'lit>>(&self, reader: &mut Reader<'_, 'lit, B>) -> Error {
+        reader.expected(Expected::Literal(Text::new(self.0.as_ref())))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::Input;
@@ -0,0 +1,191 @@
+use core::marker::PhantomData;
+
+use alloc::{fmt, format};
+
+use crate::{Buffer, Error, Expected, Reader, Span, Tokenizer};
+
+/// Name a rule in errors, see [TokenizerExt::label](crate::TokenizerExt::label)
+pub struct Labelled<T, B> {
+    tokenizer: T,
+    name: &'static str,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, B> Labelled<T, B> {
+    pub const fn new(tokenizer: T, name: &'static str) -> Labelled<T, B> {
+        Labelled {
+            tokenizer,
+            name,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, B> Clone for Labelled<T, B> {
+    fn clone(&self) -> Self {
+        Labelled {
+            tokenizer: self.tokenizer.clone(),
+            name: self.name,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, B> Copy for Labelled<T, B> {}
+
+impl<T: fmt::Debug, B> fmt::Debug for Labelled<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Labelled")
+            .field("tokenizer", &self.tokenizer)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<'input, T, B> Tokenizer<'input, B> for Labelled<T, B>
+where
+    T: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = T::Token;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        let start = reader.position();
+
+        match reader.parse(&self.tokenizer) {
+            Ok(ret) => Ok(ret),
+            // Failed at the start, so report the label instead of what the rule expected
+            Err(err) if err.position() == start && !err.is_committed() => {
+                Err(reader.expected_only(Expected::Named(self.name.into())))
+            }
+            Err(err) => {
+                let span = Span::new(start, err.position());
+                Err(err.with_context(span, format!("while parsing {}", self.name)))
+            }
+        }
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.tokenizer.peek(reader)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(self.name)
+    }
+}
+
+/// Add context to errors of a rule, see [TokenizerExt::context](crate::TokenizerExt::context)
+pub struct Context<T, B> {
+    tokenizer: T,
+    message: &'static str,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, B> Context<T, B> {
+    pub const fn new(tokenizer: T, message: &'static str) -> Context<T, B> {
+        Context {
+            tokenizer,
+            message,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, B> Clone for Context<T, B> {
+    fn clone(&self) -> Self {
+        Context {
+            tokenizer: self.tokenizer.clone(),
+            message: self.message,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, B> Copy for Context<T, B> {}
+
+impl<T: fmt::Debug, B> fmt::Debug for Context<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Context")
+            .field("tokenizer", &self.tokenizer)
+            .field("message", &self.message)
+            .finish()
+    }
+}
+
+impl<'input, T, B> Tokenizer<'input, B> for Context<T, B>
+where
+    T: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = T::Token;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        let start = reader.position();
+        self.tokenizer.to_token(reader).map_err(|err| {
+            let span = Span::new(start, err.position());
+            err.with_context(span, self.message)
+        })
+    }
+
+    fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
+        let start = reader.position();
+        self.tokenizer.eat(reader).map_err(|err| {
+            let span = Span::new(start, err.position());
+            err.with_context(span, self.message)
+        })
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.tokenizer.peek(reader)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use crate::{tokenizers::Digit, Diagnostic, Input, TokenizerExt};
+
+    use super::*;
+
+    #[test]
+    fn label() {
+        let key = ('"', Digit(10), '"').label("object key");
+
+        let mut input = Input::new("x");
+        let err = input.parse(&key).unwrap_err();
+        assert_eq!(err.to_string(), "@0: expected object key, found `x`");
+
+        let mut input = Input::new("\"1x");
+        let err = input.parse(&key).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "@2: expected `\"`, found `x`, while parsing object key"
+        );
+    }
+
+    #[test]
+    fn context() {
+        let key = ('"', Digit(10), '"').label("object key");
+        let entry = (key, ':', Digit(10)).context("in object entry");
+        let object = ('{', entry, '}').context("in object");
+
+        let mut input = Input::new("{\"1\"x}");
+        let err = input.parse(&object).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "@4: expected `:`, found `x`, in object entry, in object"
+        );
+        assert_eq!(err.context().nth(1).unwrap().0, Span::new(0, 4));
+
+        let mut input = Input::new("{\"1x");
+        let err = input.parse(&object).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "@3: expected `\"`, found `x`, while parsing object key, in object entry, in object"
+        );
+
+        let diagnostic = Diagnostic::from(&err);
+        assert_eq!(diagnostic.labels.len(), 4);
+    }
+}
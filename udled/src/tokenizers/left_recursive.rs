@@ -0,0 +1,144 @@
+use core::marker::PhantomData;
+
+use alloc::fmt;
+
+use crate::{Buffer, Error, Reader, Tokenizer};
+
+pub const fn left_recursive<T, B>(tokenizer: T, id: &'static str) -> LeftRecursive<T, B> {
+    LeftRecursive::new(tokenizer, id)
+}
+
+/// A directly left recursive rule, like `expr = expr '-' num | num`.
+///
+/// The first time the rule is parsed at a position, references to it fail,
+/// so only the non-recursive alternatives match. The rule is then parsed again,
+/// with references returning the previous result, until it stops growing.
+/// Every reference to the rule must be wrapped with the same [id],
+/// including the one starting the parse.
+/// Results are stored like with [Memoize](crate::tokenizers::Memoize)
+pub struct LeftRecursive<T, B> {
+    tokenizer: T,
+    id: &'static str,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, B> LeftRecursive<T, B> {
+    pub const fn new(tokenizer: T, id: &'static str) -> LeftRecursive<T, B> {
+        LeftRecursive {
+            tokenizer,
+            id,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, B> Clone for LeftRecursive<T, B> {
+    fn clone(&self) -> Self {
+        LeftRecursive {
+            tokenizer: self.tokenizer.clone(),
+            id: self.id,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, B> Copy for LeftRecursive<T, B> {}
+
+impl<T: fmt::Debug, B> fmt::Debug for LeftRecursive<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LeftRecursive")
+            .field("tokenizer", &self.tokenizer)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl<'input, T, B> Tokenizer<'input, B> for LeftRecursive<T, B>
+where
+    T: Tokenizer<'input, B>,
+    T::Token: Clone + 'static,
+    B: Buffer<'input>,
+{
+    type Token = T::Token;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        reader.parse_left_recursive(self.id, &self.tokenizer)
+    }
+
+    fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
+        reader.parse_left_recursive(self.id, &self.tokenizer)?;
+        Ok(())
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.tokenizer.peek(reader)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{tokenizers::Digit, AsChar, Input, IntoTokenizer, TokenizerExt};
+
+    use super::*;
+
+    // expr = expr '-' num | num
+    fn expr<'input, B>(reader: &mut Reader<'_, 'input, B>) -> Result<i64, Error>
+    where
+        B: Buffer<'input>,
+        B::Item: AsChar,
+    {
+        let num = Digit(10).map_ok(|m| m.value as i64);
+        let ret = reader.parse(
+            (expr.into_tokenizer().left_recursive("expr"), '-', &num)
+                .map_ok(|(left, _, right)| left - right)
+                .or(&num),
+        )?;
+        Ok(ret.unify())
+    }
+
+    #[test]
+    fn left_associative() {
+        let mut input = Input::new("8-2-3");
+        let ret = input
+            .parse((expr.into_tokenizer().left_recursive("expr"), crate::EOF))
+            .unwrap();
+        assert_eq!(ret.0, 3);
+
+        let mut input = Input::new("8");
+        assert_eq!(
+            input
+                .parse(expr.into_tokenizer().left_recursive("expr"))
+                .unwrap(),
+            8
+        );
+    }
+
+    #[test]
+    fn error() {
+        let mut input = Input::new("8-");
+        let err = input
+            .parse((expr.into_tokenizer().left_recursive("expr"), crate::EOF))
+            .unwrap_err();
+        assert_eq!(err.position(), 2);
+    }
+
+    #[test]
+    fn shared_id() {
+        let mut input = Input::new("8-2");
+
+        // Both rules use the id "expr" at the same position, with different tokens
+        let ret = input
+            .parse(
+                (expr.into_tokenizer().left_recursive("expr"), 'x').or((
+                    expr.into_tokenizer()
+                        .left_recursive("expr")
+                        .map_ok(|m| m as i32)
+                        .left_recursive("expr"),
+                    crate::EOF,
+                )),
+            )
+            .unwrap();
+
+        assert!(matches!(ret, crate::Either::Right((6, _))));
+    }
+}
@@ -0,0 +1,618 @@
+use core::{cell::RefCell, marker::PhantomData};
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{AsChar, Buffer, Error, Input, Item, Reader, Span, StringExt, Tokenizer, EOF};
+
+trait Rule<'input, B: Buffer<'input>, K> {
+    /// Try to match the rule at the current position.
+    /// Only committed errors are returned
+    fn lex(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Option<Item<K>>, Error>;
+
+    fn matches(&self, reader: &mut Reader<'_, 'input, B>) -> bool;
+}
+
+struct KindRule<T, F> {
+    tokenizer: T,
+    func: F,
+}
+
+impl<'input, B, K, T, F> Rule<'input, B, K> for KindRule<T, F>
+where
+    B: Buffer<'input>,
+    T: Tokenizer<'input, B>,
+    F: Fn(T::Token) -> K,
+{
+    fn lex(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Option<Item<K>>, Error> {
+        let start = reader.position();
+
+        let token = match reader.parse(&self.tokenizer) {
+            Ok(token) => token,
+            Err(err) if err.is_committed() => return Err(err),
+            Err(_) => return Ok(None),
+        };
+
+        let end = reader.position();
+        // An empty match would never advance
+        if end == start {
+            return Ok(None);
+        }
+
+        Ok(Some(Item::new(Span::new(start, end), (self.func)(token))))
+    }
+
+    fn matches(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        reader.is(&self.tokenizer)
+    }
+}
+
+/// A token with the trivia around it, produced by [Lexer::lexemes]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexeme<K> {
+    pub token: Item<K>,
+    /// Trivia before the token, after the trailing trivia of the previous token
+    pub leading: Vec<Item<K>>,
+    /// Trivia after the token, up to and including the trivia with the next
+    /// line break. The last token also gets the trivia at the end of input
+    pub trailing: Vec<Item<K>>,
+}
+
+/// What a rule does to the mode stack when it matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Stay,
+    Push(usize),
+    Pop,
+}
+
+struct Entry<'a, 'input, B, K> {
+    rule: Box<dyn Rule<'input, B, K> + 'a>,
+    action: Action,
+}
+
+struct Mode<'a, 'input, B, K> {
+    rules: Vec<Entry<'a, 'input, B, K>>,
+    trivia: Vec<Box<dyn Rule<'input, B, K> + 'a>>,
+}
+
+impl<'a, 'input, B, K> Default for Mode<'a, 'input, B, K> {
+    fn default() -> Self {
+        Mode {
+            rules: Vec::new(),
+            trivia: Vec::new(),
+        }
+    }
+}
+
+/// Split input into tokens of kind [K].
+///
+/// At each position the trivia are skipped, and then the rules are tried in the
+/// order they were added, so add keywords before identifiers. Input no rule
+/// matches becomes a single token of the error kind, and lexing continues after it.
+///
+/// Rules and trivia belong to a mode, like start conditions in flex. Lexing starts
+/// in mode 0, and a rule can [push](Lexer::push) a mode, or [pop](Lexer::pop)
+/// back to the mode it came from, so interpolations like `${...}` in template
+/// strings can be lexed with their own rules.
+///
+/// As a tokenizer it produces a `Vec<Item<K>>` without the trivia, which can be
+/// parsed in turn as a token buffer
+pub struct Lexer<'a, 'input, B, K>
+where
+    B: Buffer<'input>,
+{
+    modes: Vec<Mode<'a, 'input, B, K>>,
+    /// The mode rules are added to
+    current: usize,
+    error: K,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<'a, 'input, B, K> Lexer<'a, 'input, B, K>
+where
+    B: Buffer<'input>,
+    K: Clone + 'a,
+{
+    /// Create a lexer, using [error] as the kind of unknown input
+    pub fn new(error: K) -> Lexer<'a, 'input, B, K> {
+        Lexer {
+            modes: Vec::from([Mode::default()]),
+            current: 0,
+            error,
+            buffer: PhantomData,
+        }
+    }
+
+    /// Add the following rules and trivia to [mode]. Mode 0 is the start mode
+    pub fn mode(mut self, mode: usize) -> Self {
+        if self.modes.len() <= mode {
+            self.modes.resize_with(mode + 1, Mode::default);
+        }
+        self.current = mode;
+        self
+    }
+
+    /// Add a rule producing a token of [kind]
+    pub fn rule<T>(self, tokenizer: T, kind: K) -> Self
+    where
+        T: Tokenizer<'input, B> + 'a,
+    {
+        self.rule_with(tokenizer, move |_| kind.clone())
+    }
+
+    /// Add a rule producing a token of the kind returned by [func]
+    pub fn rule_with<T, F>(mut self, tokenizer: T, func: F) -> Self
+    where
+        T: Tokenizer<'input, B> + 'a,
+        F: Fn(T::Token) -> K + 'a,
+    {
+        self.modes[self.current].rules.push(Entry {
+            rule: Box::new(KindRule { tokenizer, func }),
+            action: Action::Stay,
+        });
+        self
+    }
+
+    /// Enter [mode] when the last added rule matches
+    ///
+    /// # Panics
+    /// If no rule was added to the current mode
+    pub fn push(mut self, mode: usize) -> Self {
+        if self.modes.len() <= mode {
+            self.modes.resize_with(mode + 1, Mode::default);
+        }
+        self.set_action(Action::Push(mode));
+        self
+    }
+
+    /// Leave the current mode when the last added rule matches.
+    /// Popping the start mode does nothing
+    ///
+    /// # Panics
+    /// If no rule was added to the current mode
+    pub fn pop(mut self) -> Self {
+        self.set_action(Action::Pop);
+        self
+    }
+
+    fn set_action(&mut self, action: Action) {
+        let entry = self.modes[self.current]
+            .rules
+            .last_mut()
+            .expect("a rule to set the mode action of");
+        entry.action = action;
+    }
+
+    /// Add trivia, like whitespace and comments, of [kind]
+    pub fn trivia<T>(mut self, tokenizer: T, kind: K) -> Self
+    where
+        T: Tokenizer<'input, B> + 'a,
+    {
+        self.modes[self.current].trivia.push(Box::new(KindRule {
+            tokenizer,
+            func: move |_| kind.clone(),
+        }));
+        self
+    }
+
+    /// A tokenizer producing the tokens with their trivia attached
+    pub fn lexemes(&self) -> Lexemes<'_, 'a, 'input, B, K> {
+        Lexemes { lexer: self }
+    }
+
+    /// Iterate the tokens of [input], without the trivia
+    pub fn iter<'l>(&'l self, input: &'l mut Input<B>) -> Tokens<'l, 'a, 'input, B, K> {
+        Tokens {
+            lexer: self,
+            input,
+            stack: RefCell::new(Vec::new()),
+            done: false,
+        }
+    }
+
+    /// Lex the next token or trivia in the mode on top of [stack].
+    /// Returns None at the end of input
+    fn next(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        stack: &mut Vec<usize>,
+    ) -> Result<Option<(Item<K>, bool)>, Error> {
+        let mode = &self.modes[stack.last().copied().unwrap_or_default()];
+
+        for trivia in &mode.trivia {
+            if let Some(item) = trivia.lex(reader)? {
+                return Ok(Some((item, true)));
+            }
+        }
+
+        for entry in &mode.rules {
+            let Some(item) = entry.rule.lex(reader)? else {
+                continue;
+            };
+
+            match entry.action {
+                Action::Stay => {}
+                Action::Push(mode) => stack.push(mode),
+                Action::Pop => {
+                    stack.pop();
+                }
+            }
+
+            return Ok(Some((item, false)));
+        }
+
+        if reader.is(EOF) {
+            return Ok(None);
+        }
+
+        let start = reader.position();
+        reader.read()?;
+
+        while !reader.is(EOF) && !Self::matches(mode, reader) {
+            reader.read()?;
+        }
+
+        let span = Span::new(start, reader.position());
+        Ok(Some((Item::new(span, self.error.clone()), false)))
+    }
+
+    fn matches(mode: &Mode<'a, 'input, B, K>, reader: &mut Reader<'_, 'input, B>) -> bool {
+        mode.trivia.iter().any(|m| m.matches(reader))
+            || mode.rules.iter().any(|m| m.rule.matches(reader))
+    }
+
+    fn next_token(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        stack: &mut Vec<usize>,
+    ) -> Result<Option<Item<K>>, Error> {
+        loop {
+            match self.next(reader, stack)? {
+                Some((_, true)) => continue,
+                Some((item, false)) => return Ok(Some(item)),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<'a, 'input, B, K> Tokenizer<'input, B> for Lexer<'a, 'input, B, K>
+where
+    B: Buffer<'input>,
+    K: Clone + 'a,
+{
+    type Token = Vec<Item<K>>;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        let mut stack = Vec::new();
+        let mut tokens = Vec::new();
+        while let Some(item) = self.next_token(reader, &mut stack)? {
+            tokens.push(item);
+        }
+        Ok(tokens)
+    }
+}
+
+/// Tokenizer returned by [Lexer::lexemes]
+pub struct Lexemes<'l, 'a, 'input, B, K>
+where
+    B: Buffer<'input>,
+{
+    lexer: &'l Lexer<'a, 'input, B, K>,
+}
+
+impl<'l, 'a, 'input, B, K> Tokenizer<'input, B> for Lexemes<'l, 'a, 'input, B, K>
+where
+    B: Buffer<'input>,
+    B::Item: AsChar,
+    K: Clone + 'a,
+{
+    type Token = Vec<Lexeme<K>>;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        let mut lexemes: Vec<Lexeme<K>> = Vec::new();
+        let mut trivia = Vec::new();
+        let mut stack = Vec::new();
+        // Whether the trivia is on the line of the last token
+        let mut same_line = false;
+
+        loop {
+            let checkpoint = reader.checkpoint();
+            let Some((item, is_trivia)) = self.lexer.next(reader, &mut stack)? else {
+                break;
+            };
+
+            if !is_trivia {
+                lexemes.push(Lexeme {
+                    token: item,
+                    leading: core::mem::take(&mut trivia),
+                    trailing: Vec::new(),
+                });
+                same_line = true;
+                continue;
+            }
+
+            match lexemes.last_mut() {
+                Some(last) if same_line => {
+                    same_line = !reader
+                        .items_since(&checkpoint)
+                        .any(|m| m.as_char().is_some_and(|m| m.is_linebreak()));
+                    last.trailing.push(item);
+                }
+                _ => trivia.push(item),
+            }
+        }
+
+        if let Some(last) = lexemes.last_mut() {
+            last.trailing.append(&mut trivia);
+        }
+
+        Ok(lexemes)
+    }
+}
+
+struct NextToken<'l, 'a, 'input, B, K>
+where
+    B: Buffer<'input>,
+{
+    lexer: &'l Lexer<'a, 'input, B, K>,
+    stack: &'l RefCell<Vec<usize>>,
+}
+
+impl<'l, 'a, 'input, B, K> Tokenizer<'input, B> for NextToken<'l, 'a, 'input, B, K>
+where
+    B: Buffer<'input>,
+    K: Clone + 'a,
+{
+    type Token = Option<Item<K>>;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        self.lexer.next_token(reader, &mut self.stack.borrow_mut())
+    }
+}
+
+/// Iterator returned by [Lexer::iter]
+pub struct Tokens<'l, 'a, 'input, B, K>
+where
+    B: Buffer<'input>,
+{
+    lexer: &'l Lexer<'a, 'input, B, K>,
+    input: &'l mut Input<B>,
+    stack: RefCell<Vec<usize>>,
+    done: bool,
+}
+
+impl<'l, 'a, 'input, B, K> Iterator for Tokens<'l, 'a, 'input, B, K>
+where
+    B: Buffer<'input>,
+    K: Clone + 'a,
+{
+    type Item = Result<Item<K>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let ret = self.input.parse(NextToken {
+            lexer: self.lexer,
+            stack: &self.stack,
+        });
+
+        match ret {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec};
+
+    use crate::{
+        tokenizers::{Alphabetic, Digit, Kind, Puntuated, WhiteSpace},
+        TokenizerExt, EOF,
+    };
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Token {
+        Let,
+        Ident,
+        Number,
+        Comma,
+        Space,
+        Comment,
+        Error,
+    }
+
+    fn lexer<'input>() -> Lexer<'input, 'input, crate::buffer::StringBuffer<'input>, Token> {
+        Lexer::new(Token::Error)
+            .trivia(WhiteSpace.many(), Token::Space)
+            .trivia(("#", Alphabetic.many()), Token::Comment)
+            .rule("let", Token::Let)
+            .rule(Alphabetic.many(), Token::Ident)
+            .rule(Digit(10).many(), Token::Number)
+            .rule(',', Token::Comma)
+    }
+
+    #[test]
+    fn tokens() {
+        let lexer = lexer();
+        let mut input = Input::new("let a  1,%%2 #end");
+
+        let tokens = input.parse(&lexer).unwrap();
+        let kinds = tokens.iter().map(|m| m.value).collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            [
+                Token::Let,
+                Token::Ident,
+                Token::Number,
+                Token::Comma,
+                Token::Error,
+                Token::Number
+            ]
+        );
+        assert_eq!(tokens[4].span, Span::new(9, 11));
+
+        // The tokens can be parsed in turn
+        let mut input = Input::new(&tokens);
+        let err = input
+            .parse((
+                Kind(Token::Let),
+                Kind(Token::Ident),
+                Puntuated::new(Kind(Token::Number), Kind(Token::Comma)),
+                EOF,
+            ))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "@9: expected Number, found Error");
+    }
+
+    #[test]
+    fn iter() {
+        let lexer = lexer();
+        let mut input = Input::new("a 1");
+
+        let tokens = lexer
+            .iter(&mut input)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Item::new(Span::new(0, 1), Token::Ident),
+                Item::new(Span::new(2, 3), Token::Number)
+            ]
+        );
+    }
+
+    #[test]
+    fn trivia() {
+        let lexer = lexer();
+        let mut input = Input::new(" a #x b ");
+
+        let lexemes = input.parse(lexer.lexemes()).unwrap();
+        assert_eq!(lexemes.len(), 2);
+        assert_eq!(
+            lexemes[0].leading,
+            [Item::new(Span::new(0, 1), Token::Space)]
+        );
+        assert_eq!(
+            lexemes[0].trailing,
+            [
+                Item::new(Span::new(2, 3), Token::Space),
+                Item::new(Span::new(3, 5), Token::Comment),
+                Item::new(Span::new(5, 6), Token::Space)
+            ]
+        );
+        assert!(lexemes[1].leading.is_empty());
+        assert_eq!(
+            lexemes[1].trailing,
+            vec![Item::new(Span::new(7, 8), Token::Space)]
+        );
+    }
+
+    #[test]
+    fn trailing_trivia_ends_at_line_break() {
+        let lexer = lexer();
+        let mut input = Input::new("a\nb #x\n#y\nc");
+
+        let lexemes = input.parse(lexer.lexemes()).unwrap();
+        assert_eq!(lexemes.len(), 3);
+
+        // The trivia up to the line break belongs to the middle token
+        assert!(lexemes[1].leading.is_empty());
+        assert_eq!(
+            lexemes[1].trailing,
+            [
+                Item::new(Span::new(3, 4), Token::Space),
+                Item::new(Span::new(4, 6), Token::Comment),
+                Item::new(Span::new(6, 7), Token::Space)
+            ]
+        );
+        assert_eq!(
+            lexemes[2].leading,
+            [
+                Item::new(Span::new(7, 9), Token::Comment),
+                Item::new(Span::new(9, 10), Token::Space)
+            ]
+        );
+
+        // Unicode line separators end it too
+        let mut input = Input::new("a\u{2028}#x\nb");
+        let lexemes = input.parse(lexer.lexemes()).unwrap();
+        assert_eq!(
+            lexemes[0].trailing,
+            [Item::new(Span::new(1, 4), Token::Space)]
+        );
+        assert_eq!(
+            lexemes[1].leading,
+            [
+                Item::new(Span::new(4, 6), Token::Comment),
+                Item::new(Span::new(6, 7), Token::Space)
+            ]
+        );
+    }
+
+    #[test]
+    fn modes() {
+        const TEMPLATE: usize = 1;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Token {
+            Tick,
+            Text,
+            Open,
+            Close,
+            Ident,
+            Error,
+        }
+
+        let lexer = Lexer::new(Token::Error)
+            .trivia(WhiteSpace.many(), Token::Text)
+            .rule('`', Token::Tick)
+            .push(TEMPLATE)
+            .rule('}', Token::Close)
+            .pop()
+            .rule(Alphabetic.many(), Token::Ident)
+            .mode(TEMPLATE)
+            .rule('`', Token::Tick)
+            .pop()
+            .rule("${", Token::Open)
+            .push(0)
+            .rule(Alphabetic.or(' ').many(), Token::Text);
+
+        let mut input = Input::new("`a b${ c }d` e");
+        let tokens = lexer
+            .iter(&mut input)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let kinds = tokens.iter().map(|m| m.value).collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            [
+                Token::Tick,
+                Token::Text,
+                Token::Open,
+                Token::Ident,
+                Token::Close,
+                Token::Text,
+                Token::Tick,
+                Token::Ident
+            ]
+        );
+        assert_eq!(tokens[1].span, Span::new(1, 4));
+    }
+}
@@ -0,0 +1,112 @@
+use core::marker::PhantomData;
+
+use alloc::fmt;
+
+use crate::{Buffer, Error, Reader, Tokenizer};
+
+/// A sequence of tokenizers, like a tuple, which can be peeked a number of tokens ahead
+pub trait Sequence<'input, B: Buffer<'input>>: Tokenizer<'input, B> {
+    /// Match the first [n] - 1 tokens, and peek the token after them.
+    /// The reader may be moved, so call it through [Reader::is]
+    fn peek_n(&self, reader: &mut Reader<'_, 'input, B>, n: usize) -> bool;
+}
+
+/// Peek [K] tokens of the sequence [T], instead of only the first.
+///
+/// `('-', Digit(10)).lookahead::<2>()` peeks a minus followed by a digit,
+/// so an optional number is not committed to on a lone minus.
+/// With `K = usize::MAX` the whole sequence is matched
+pub struct Lookahead<T, const K: usize, B> {
+    tokenizer: T,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, const K: usize, B> Lookahead<T, K, B> {
+    pub const fn new(tokenizer: T) -> Lookahead<T, K, B> {
+        Lookahead {
+            tokenizer,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, const K: usize, B> Clone for Lookahead<T, K, B> {
+    fn clone(&self) -> Self {
+        Lookahead {
+            tokenizer: self.tokenizer.clone(),
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, const K: usize, B> Copy for Lookahead<T, K, B> {}
+
+impl<T: fmt::Debug, const K: usize, B> fmt::Debug for Lookahead<T, K, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Lookahead")
+            .field("tokenizer", &self.tokenizer)
+            .field("k", &K)
+            .finish()
+    }
+}
+
+impl<'input, T, const K: usize, B> Tokenizer<'input, B> for Lookahead<T, K, B>
+where
+    T: Sequence<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = T::Token;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        self.tokenizer.to_token(reader)
+    }
+
+    fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
+        self.tokenizer.eat(reader)
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        reader.is(PeekN(&self.tokenizer, K))
+    }
+}
+
+/// Peeks [Sequence::peek_n] inside a reader which is rewound
+struct PeekN<'a, T>(&'a T, usize);
+
+impl<'input, T, B> Tokenizer<'input, B> for PeekN<'_, T>
+where
+    T: Sequence<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = ();
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        self.0.eat(reader)
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.0.peek_n(reader, self.1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{tokenizers::Digit, Input, TokenizerExt};
+
+    #[test]
+    fn peek_k() {
+        let mut input = Input::new("-x");
+
+        assert!(input.is(('-', Digit(10))));
+        assert!(input.is(('-', Digit(10)).lookahead::<1>()));
+        assert!(!input.is(('-', Digit(10)).lookahead::<2>()));
+        assert!(!input.is(('-', 'x', 'y').lookahead::<{ usize::MAX }>()));
+        assert!(input.is(('-', 'x', 'y').lookahead::<2>()));
+
+        // The optional number is not committed to
+        let number = ('-', Digit(10)).lookahead::<2>().optional();
+        assert!(input.parse(number).unwrap().is_none());
+        assert!(input.parse(('-', Digit(10)).optional()).is_err());
+        assert_eq!(input.position(), 0);
+    }
+}
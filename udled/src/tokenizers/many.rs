@@ -59,10 +59,11 @@ where
                 break;
             }
 
-            let Ok(next) = reader.parse(&self.tokenizer) else {
-                break;
-            };
-            output.push(next);
+            match reader.parse(&self.tokenizer) {
+                Ok(next) => output.push(next),
+                Err(err) if err.is_committed() => return Err(err),
+                Err(_) => break,
+            }
         }
 
         let end = reader.position();
@@ -74,8 +75,10 @@ where
         reader.eat(&self.tokenizer)?;
 
         loop {
-            if reader.eat(&self.tokenizer).is_err() {
-                break;
+            match reader.eat(&self.tokenizer) {
+                Ok(_) => {}
+                Err(err) if err.is_committed() => return Err(err),
+                Err(_) => break,
             }
         }
 
@@ -0,0 +1,160 @@
+use core::marker::PhantomData;
+
+use alloc::fmt;
+
+use crate::{Buffer, Error, Reader, Tokenizer};
+
+pub const fn memoize<T, B>(tokenizer: T, id: &'static str) -> Memoize<T, B> {
+    Memoize::new(tokenizer, id)
+}
+
+/// Remember the result of [T] at each position of the input.
+///
+/// When [T] is parsed again at the same position, like when an alternative
+/// backtracks, the stored result is returned without running [T].
+/// Results are keyed by [id], the token type and position, so every memoized
+/// rule needs its own id, and each reference to the same rule must use the same id.
+/// Tokens must be `'static`, so a rule borrowing the input can memoize its span
+/// and slice the input again. The memo is cleared after each parse
+pub struct Memoize<T, B> {
+    tokenizer: T,
+    id: &'static str,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, B> Memoize<T, B> {
+    pub const fn new(tokenizer: T, id: &'static str) -> Memoize<T, B> {
+        Memoize {
+            tokenizer,
+            id,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, B> Clone for Memoize<T, B> {
+    fn clone(&self) -> Self {
+        Memoize {
+            tokenizer: self.tokenizer.clone(),
+            id: self.id,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, B> Copy for Memoize<T, B> {}
+
+impl<T: fmt::Debug, B> fmt::Debug for Memoize<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Memoize")
+            .field("tokenizer", &self.tokenizer)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl<'input, T, B> Tokenizer<'input, B> for Memoize<T, B>
+where
+    T: Tokenizer<'input, B>,
+    T::Token: Clone + 'static,
+    B: Buffer<'input>,
+{
+    type Token = T::Token;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        reader.parse_memoized(self.id, &self.tokenizer)
+    }
+
+    fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
+        reader.parse_memoized(self.id, &self.tokenizer)?;
+        Ok(())
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.tokenizer.peek(reader)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use alloc::string::String;
+
+    use crate::{
+        tokenizers::{Alphabetic, Digit},
+        AsChar, Either, Input, IntoTokenizer, TokenizerExt,
+    };
+
+    use super::*;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn number<'input, B>(reader: &mut Reader<'_, 'input, B>) -> Result<u32, Error>
+    where
+        B: Buffer<'input>,
+        B::Item: AsChar,
+    {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        let digits = reader.parse(Digit(10).many())?;
+        Ok(digits.value.iter().fold(0, |acc, m| acc * 10 + m.value))
+    }
+
+    #[test]
+    fn backtracking() {
+        let mut input = Input::new("123y");
+
+        let number = number.into_tokenizer().memoize("number");
+        let ret = input
+            .parse((number, 'x').or((number, 'y')).or((number, 'z')))
+            .unwrap();
+
+        assert!(matches!(ret, Either::Left(Either::Right((123, _)))));
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+        // The memo does not outlive the parse
+        let mut input = Input::new("42");
+        assert_eq!(input.parse(number).unwrap(), 42);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn same_type() {
+        let mut input = Input::new("9y");
+
+        let ret = input
+            .parse((Digit(8).memoize("octal"), 'x').or((Digit(10).memoize("decimal"), 'y')))
+            .unwrap();
+
+        assert!(matches!(ret, Either::Right((m, _)) if m.value == 9));
+    }
+
+    #[test]
+    fn borrowed_input() {
+        let source = String::from("word!");
+        let mut input = Input::new(source.as_str());
+
+        let word = Alphabetic.many().spanned().memoize("word");
+        let ret = input.parse((word, '?').or((word, '!'))).unwrap();
+
+        let Either::Right((span, _)) = ret else {
+            panic!("expected the second alternative");
+        };
+        assert_eq!(span.slice(&source), Some("word"));
+    }
+
+    #[test]
+    fn shared_id() {
+        let mut input = Input::new("1y");
+
+        // The lookup of the second rule misses, as its token type differs
+        let ret = input
+            .parse(
+                (Digit(10).memoize("number"), 'x')
+                    .or((Digit(10).map_ok(|m| m.value).memoize("number"), 'y')),
+            )
+            .unwrap();
+
+        assert!(matches!(ret, Either::Right((1, _))));
+    }
+}
@@ -1,31 +1,59 @@
 mod chars;
+mod cut;
+mod delimited;
 mod digit;
 mod exclude;
+mod expression;
 mod ignore_case;
+mod label;
+mod left_recursive;
+mod lexer;
+mod lookahead;
 mod many;
+mod memoize;
+mod named;
 mod next;
 mod not;
 mod opt;
 mod or;
 mod peek;
 mod punctuated;
+mod recover;
+mod repeat;
+mod skip;
 mod slice;
 mod span;
+mod token;
 mod until;
 
+pub(crate) use self::skip::Skip;
+
 pub use self::{
     chars::*,
+    cut::*,
+    delimited::*,
     digit::{AsDigits, Digit},
     exclude::Exclude,
+    expression::{Assoc, Expression},
     ignore_case::*,
+    label::{Context, Labelled},
+    left_recursive::*,
+    lexer::{Lexeme, Lexemes, Lexer, Tokens},
+    lookahead::{Lookahead, Sequence},
     many::*,
+    memoize::*,
+    named::*,
     next::Next,
     not::*,
     opt::*,
     or::*,
     peek::*,
     punctuated::*,
+    recover::*,
+    repeat::RepeatRange,
+    skip::{NoSkip, Skipping},
     slice::Sliced,
     span::*,
+    token::*,
     until::*,
 };
@@ -0,0 +1,71 @@
+use core::marker::PhantomData;
+
+use alloc::fmt;
+
+use crate::{Buffer, Error, Reader, Tokenizer};
+
+pub const fn named<T, B>(tokenizer: T, name: &'static str) -> Named<T, B> {
+    Named::new(tokenizer, name)
+}
+
+/// Give [T] a name, shown in traces instead of its type
+pub struct Named<T, B> {
+    tokenizer: T,
+    name: &'static str,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, B> Named<T, B> {
+    pub const fn new(tokenizer: T, name: &'static str) -> Named<T, B> {
+        Named {
+            tokenizer,
+            name,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, B> Clone for Named<T, B> {
+    fn clone(&self) -> Self {
+        Named {
+            tokenizer: self.tokenizer.clone(),
+            name: self.name,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, B> Copy for Named<T, B> {}
+
+impl<T: fmt::Debug, B> fmt::Debug for Named<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Named")
+            .field("tokenizer", &self.tokenizer)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<'input, T, B> Tokenizer<'input, B> for Named<T, B>
+where
+    T: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = T::Token;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        self.tokenizer.to_token(reader)
+    }
+
+    fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
+        self.tokenizer.eat(reader)
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.tokenizer.peek(reader)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(self.name)
+    }
+}
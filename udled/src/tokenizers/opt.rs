@@ -49,10 +49,10 @@ where
     type Token = Option<T::Token>;
 
     fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
-        if reader.is(&self.tokenizer) {
-            Ok(Some(reader.parse(&self.tokenizer)?))
-        } else {
-            Ok(None)
+        match reader.attempt(&self.tokenizer) {
+            (Ok(ret), _) => Ok(Some(ret)),
+            (Err(err), consumed) if fails(reader, &self.tokenizer, &err, consumed) => Err(err),
+            (Err(_), _) => Ok(None),
         }
     }
 
@@ -61,9 +61,72 @@ where
     }
 
     fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
-        if reader.is(&self.tokenizer) {
-            reader.eat(&self.tokenizer)?;
+        match reader.attempt_eat(&self.tokenizer) {
+            (Err(err), consumed) if fails(reader, &self.tokenizer, &err, consumed) => Err(err),
+            _ => Ok(()),
         }
-        Ok(())
+    }
+}
+
+/// Whether a failed attempt of an optional [tokenizer] is an error.
+/// A tokenizer which did not consume input is missing, so it is only
+/// peeked again when it failed part way
+pub(crate) fn fails<'input, T, B>(
+    reader: &mut Reader<'_, 'input, B>,
+    tokenizer: &T,
+    error: &Error,
+    consumed: bool,
+) -> bool
+where
+    T: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    error.is_committed() || (consumed && reader.is(tokenizer))
+}
+
+#[cfg(test)]
+mod test {
+    use core::cell::Cell;
+
+    use crate::{AsChar, Input, TokenizerExt};
+
+    use super::*;
+
+    /// Matches "ab", counting how often it runs. Peeks like a tuple, at "a" only
+    struct Counted<'a>(&'a Cell<usize>);
+
+    impl<'input, B> Tokenizer<'input, B> for Counted<'_>
+    where
+        B: Buffer<'input>,
+        B::Item: AsChar,
+    {
+        type Token = ();
+
+        fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+            self.0.set(self.0.get() + 1);
+            reader.eat(('a', 'b'))
+        }
+
+        fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+            reader.is('a')
+        }
+    }
+
+    #[test]
+    fn single_pass() {
+        let count = Cell::new(0);
+
+        let mut input = Input::new("ab");
+        assert_eq!(input.parse(Counted(&count).optional()).unwrap(), Some(()));
+        assert_eq!(count.get(), 1);
+
+        count.set(0);
+        let mut input = Input::new("x");
+        assert_eq!(input.parse(Counted(&count).optional()).unwrap(), None);
+        assert_eq!(count.get(), 1);
+
+        // A partial match fails
+        let mut input = Input::new("ac");
+        assert!(input.parse(Counted(&count).optional()).is_err());
     }
 }
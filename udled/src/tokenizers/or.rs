@@ -1,6 +1,6 @@
 use core::marker::PhantomData;
 
-use alloc::{fmt, vec};
+use alloc::fmt;
 
 use crate::{Buffer, Either, Error, Reader, Tokenizer};
 
@@ -56,6 +56,7 @@ where
     fn to_token<'a>(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
         let left_err = match reader.parse(&self.left) {
             Ok(ret) => return Ok(Either::Left(ret)),
+            Err(err) if err.is_committed() => return Err(err),
             Err(err) => err,
         };
 
@@ -64,12 +65,13 @@ where
             Err(err) => err,
         };
 
-        Err(reader.error_with("either", vec![left_err, right_err]))
+        Err(left_err.merge_alternative(right_err))
     }
 
     fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
         let left_err = match reader.eat(&self.left) {
             Ok(_) => return Ok(()),
+            Err(err) if err.is_committed() => return Err(err),
             Err(err) => err,
         };
 
@@ -78,10 +80,118 @@ where
             Err(err) => err,
         };
 
-        Err(reader.error_with("either", vec![left_err, right_err]))
+        Err(left_err.merge_alternative(right_err))
     }
 
     fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
         reader.is(&self.left) || reader.is(&self.right)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use crate::test_alloc::allocations;
+
+    use crate::{
+        any,
+        tokenizers::{Digit, Exclude, Not},
+        Expected, Input, TokenizerExt,
+    };
+
+    #[test]
+    fn expected_one_of() {
+        let mut input = Input::new("}");
+        let err = input.parse(any!(']', ',', "end")).unwrap_err();
+
+        assert_eq!(err.position(), 0);
+        assert_eq!(
+            err.expected_items(),
+            &[
+                Expected::Char(']'),
+                Expected::Char(','),
+                Expected::Literal("end".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn furthest_failure() {
+        let mut input = Input::new("[12}");
+        let err = input
+            .parse(('[', Digit(10).many(), any!(']', ',')))
+            .unwrap_err();
+
+        assert_eq!(err.position(), 3);
+        assert_eq!(
+            err.to_string(),
+            "@3: expected one of `]`, digit, `,` but found `}`"
+        );
+    }
+
+    #[test]
+    fn backtracking_does_not_allocate() {
+        let alternatives = any!('a', 'c', 'b');
+        let mut input = Input::new("bbbb");
+
+        // The first parse allocates the tracked failure, which is reused
+        input.parse(alternatives).unwrap();
+
+        let count = allocations(|| {
+            assert!(input.is(alternatives));
+            assert!(!input.is('a'));
+            input.parse(alternatives).unwrap();
+            input.parse(Digit(10).or('b')).unwrap();
+        });
+
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn exclude_does_not_allocate() {
+        let other = Exclude::new('b').or(Not('b'));
+        let mut input = Input::new("bb");
+
+        assert!(input.parse(other).is_err());
+
+        let count = allocations(|| {
+            input.reset();
+            assert!(input.parse(other).is_err());
+            input.reset();
+            input.parse(Exclude::new('a').or('b')).unwrap();
+        });
+
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn failing_alternatives_do_not_allocate() {
+        let keyword = any!("let", "fn", Digit(10)).or("return");
+        let mut input = Input::new("return");
+
+        input.parse(keyword).unwrap();
+
+        let count = allocations(|| {
+            input.reset();
+            input.parse(keyword).unwrap();
+            input.reset();
+            input.parse(any!("let", "fn").optional()).unwrap();
+        });
+
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn failing_alternatives_are_combined() {
+        let mut input = Input::new("x");
+        let err = input
+            .parse(any!("let", "fn", Digit(10)).or("return"))
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "@0: expected one of `let`, `fn`, digit, `return` but found `x`"
+        );
+    }
+}
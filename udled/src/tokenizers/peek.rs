@@ -1,5 +1,6 @@
 use crate::{Buffer, Reader, Result, Tokenizer};
 
+/// Peeks by matching all of [T], for exact lookahead
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Peek<T>(pub T);
 
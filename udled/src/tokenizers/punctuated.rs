@@ -1,6 +1,8 @@
 use alloc::vec::Vec;
 
-use crate::{tokenizers::Peek, Buffer, Error, Reader, Span, Tokenizer, WithSpan};
+use crate::{Buffer, Error, Reader, Span, Tokenizer, WithSpan};
+
+use super::{opt::fails, repeat::too_few};
 
 #[derive(Debug, Clone, Copy)]
 pub enum PuntuatedItem<T, P> {
@@ -38,6 +40,37 @@ impl<T, P> PunctuatedList<T, P> {
         })
     }
 
+    /// Iterate the items with the separator following them.
+    /// Only the last item can be without a separator
+    pub fn pairs(&self) -> impl Iterator<Item = (&T, Option<&P>)> {
+        self.list.chunks(2).filter_map(|pair| match pair {
+            [PuntuatedItem::Item(item), PuntuatedItem::Punct(punct)] => Some((item, Some(punct))),
+            [PuntuatedItem::Item(item)] => Some((item, None)),
+            _ => None,
+        })
+    }
+
+    pub fn into_pairs(self) -> impl Iterator<Item = (T, Option<P>)> {
+        let mut list = self.list.into_iter().peekable();
+        core::iter::from_fn(move || {
+            let PuntuatedItem::Item(item) = list.next()? else {
+                return None;
+            };
+            match list.next_if(|m| matches!(m, PuntuatedItem::Punct(_))) {
+                Some(PuntuatedItem::Punct(punct)) => Some((item, Some(punct))),
+                _ => Some((item, None)),
+            }
+        })
+    }
+
+    /// The separator after the last item, if any
+    pub fn trailing(&self) -> Option<&P> {
+        match self.list.last() {
+            Some(PuntuatedItem::Punct(punct)) => Some(punct),
+            _ => None,
+        }
+    }
+
     pub fn iter(&self) -> core::slice::Iter<'_, PuntuatedItem<T, P>> {
         self.list.iter()
     }
@@ -57,11 +90,32 @@ impl<T, P> WithSpan for PunctuatedList<T, P> {
     }
 }
 
+/// Whether a list can end with a separator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trailing {
+    /// A separator must be followed by an item
+    Forbidden,
+    /// The list can end with a separator
+    Allowed,
+    /// Every item must be followed by a separator
+    Required,
+}
+
+/// Matches items of [T] separated by [P].
+///
+/// The default, without a [Trailing] policy, is lenient: a separator that is
+/// not followed by an item is not an error. It is left in the input, so `1,x`
+/// matches `1` and a following tokenizer sees `,x`. With a policy the separator
+/// is consumed, and [Trailing::Forbidden] fails with the error of the item.
+/// Set what the items are called in errors with [with_name](Puntuated::with_name)
 #[derive(Debug, Clone, Copy)]
 pub struct Puntuated<T, P> {
     item: T,
     punct: P,
-    non_empty: bool,
+    trailing: Option<Trailing>,
+    min: usize,
+    max: Option<usize>,
+    name: &'static str,
 }
 
 impl<T, P> Puntuated<T, P> {
@@ -69,9 +123,40 @@ impl<T, P> Puntuated<T, P> {
         Puntuated {
             item,
             punct,
-            non_empty: false,
+            trailing: None,
+            min: 0,
+            max: None,
+            name: "items",
         }
     }
+
+    /// Name of the items used in errors. Defaults to "items"
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn trailing(mut self, trailing: Trailing) -> Self {
+        self.trailing = Some(trailing);
+        self
+    }
+
+    /// Require at least one item
+    pub fn non_empty(self) -> Self {
+        self.min(1)
+    }
+
+    /// Require at least [min] items
+    pub fn min(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Stop after [max] items
+    pub fn max(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
 }
 
 impl<'input, T, P, B> Tokenizer<'input, B> for Puntuated<T, P>
@@ -86,27 +171,90 @@ where
         let start = reader.position();
         let mut output = Vec::new();
 
-        if self.non_empty {
-            let item = reader.parse(&self.item)?;
-            output.push(PuntuatedItem::Item(item));
-            if reader.is(Peek((&self.punct, &self.item))) {
-                let punct = reader.parse(&self.punct)?;
-                output.push(PuntuatedItem::Punct(punct));
-            }
+        if self.max == Some(0) {
+            return Ok(PunctuatedList {
+                list: output,
+                span: Span::new(start, start),
+            });
         }
 
-        loop {
-            if !reader.is(&self.item) {
-                break;
+        let first = match reader.attempt(&self.item) {
+            (Ok(item), _) => item,
+            (Err(err), consumed) if self.min > 0 || fails(reader, &self.item, &err, consumed) => {
+                return Err(err)
+            }
+            (Err(_), _) => {
+                return Ok(PunctuatedList {
+                    list: output,
+                    span: Span::new(start, start),
+                })
             }
+        };
+
+        output.push(PuntuatedItem::Item(first));
+        let mut count = 1;
+
+        // The failure which ended the list
+        let mut error = None;
 
-            let item = reader.parse(&self.item)?;
+        while self.max.map_or(true, |max| count < max) {
+            let Some(trailing) = self.trailing else {
+                match reader.parse((&self.punct, &self.item)) {
+                    Ok((punct, item)) => {
+                        output.push(PuntuatedItem::Punct(punct));
+                        output.push(PuntuatedItem::Item(item));
+                        count += 1;
+                        continue;
+                    }
+                    Err(err) if err.is_committed() => return Err(err),
+                    Err(err) => {
+                        error = Some(err);
+                        break;
+                    }
+                }
+            };
 
-            output.push(PuntuatedItem::Item(item));
+            let punct = match reader.parse(&self.punct) {
+                Ok(punct) => punct,
+                Err(err) if err.is_committed() => return Err(err),
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                }
+            };
 
-            if reader.is(Peek((&self.punct, &self.item))) {
-                let punct = reader.parse(&self.punct)?;
-                output.push(PuntuatedItem::Punct(punct));
+            output.push(PuntuatedItem::Punct(punct));
+
+            match reader.parse(&self.item) {
+                Ok(item) => {
+                    output.push(PuntuatedItem::Item(item));
+                    count += 1;
+                }
+                Err(err) if err.is_committed() || trailing == Trailing::Forbidden => {
+                    return Err(err)
+                }
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                }
+            }
+        }
+
+        if count < self.min {
+            let error = error.unwrap_or_else(|| reader.error("expected more items"));
+            return Err(too_few(error, count, self.min, self.max, self.name));
+        }
+
+        let ends_with_punct = matches!(output.last(), Some(PuntuatedItem::Punct(_)));
+
+        if !ends_with_punct && matches!(self.trailing, Some(Trailing::Allowed | Trailing::Required))
+        {
+            match reader.parse(&self.punct) {
+                Ok(punct) => output.push(PuntuatedItem::Punct(punct)),
+                Err(err) if err.is_committed() || self.trailing == Some(Trailing::Required) => {
+                    return Err(err)
+                }
+                Err(_) => {}
             }
         }
 
@@ -118,3 +266,109 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use crate::{tokenizers::Digit, Input};
+
+    use super::*;
+
+    #[test]
+    fn trailing() {
+        let list = Puntuated::new(Digit(10), ',');
+
+        let mut input = Input::new("1,2,]");
+        let ret = input.parse(list).unwrap();
+        assert_eq!(ret.len(), 3);
+        assert!(ret.trailing().is_none());
+
+        let mut input = Input::new("1,2,]");
+        let ret = input.parse(list.trailing(Trailing::Allowed)).unwrap();
+        assert_eq!(ret.len(), 4);
+        assert!(ret.trailing().is_some());
+
+        let mut input = Input::new("1,2,]");
+        let err = input.parse(list.trailing(Trailing::Forbidden)).unwrap_err();
+        assert_eq!(err.to_string(), "@4: expected digit, found `]`");
+
+        let mut input = Input::new("1,2]");
+        let err = input.parse(list.trailing(Trailing::Required)).unwrap_err();
+        assert_eq!(err.to_string(), "@3: expected `,`, found `]`");
+    }
+
+    #[test]
+    fn counts() {
+        let list = Puntuated::new(Digit(10), ',');
+
+        let mut input = Input::new("]");
+        assert!(input.parse(list).unwrap().is_empty());
+        assert!(input.parse(list.non_empty()).is_err());
+
+        let mut input = Input::new("1,2]");
+        let err = input.parse(list.min(3)).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("@3: expected at least 3 items, found 2"));
+
+        let ret = input.parse(list.max(1)).unwrap();
+        assert_eq!(ret.len(), 1);
+
+        input.reset();
+        let err = input.parse(list.min(3).with_name("digits")).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("@3: expected at least 3 digits, found 2"));
+
+        let err = input.parse(list.min(3).max(3)).unwrap_err();
+        assert!(err.to_string().starts_with("@3: expected 3 items, found 2"));
+    }
+
+    #[test]
+    fn separator_without_item() {
+        let list = Puntuated::new(Digit(10), ',');
+
+        // The separator is left for the next tokenizer
+        let mut input = Input::new("1,2,]");
+        let (ret, _, _) = input.parse((list, ',', ']')).unwrap();
+        assert_eq!(ret.len(), 3);
+        assert_eq!(ret.span(), Span::new(0, 3));
+
+        let mut input = Input::new("1,2,]");
+        // Otherwise the furthest failure is the missing item
+        let err = input.parse((list, ']')).unwrap_err();
+        assert_eq!(err.to_string(), "@4: expected digit, found `]`");
+    }
+
+    #[test]
+    fn default_policy() {
+        let list = Puntuated::new(Digit(10), ',');
+
+        let mut input = Input::new("1,x");
+        let ret = input.parse(list).unwrap();
+        assert_eq!(ret.len(), 1);
+        assert_eq!(input.position(), 1);
+
+        input.reset();
+        let err = input.parse((list, crate::EOF)).unwrap_err();
+        assert_eq!(err.to_string(), "@2: expected digit, found `x`");
+    }
+
+    #[test]
+    fn pairs() {
+        let mut input = Input::new("1,2,3,");
+        let ret = input
+            .parse(Puntuated::new(Digit(10), ',').trailing(Trailing::Allowed))
+            .unwrap();
+
+        let pairs = ret
+            .pairs()
+            .map(|(item, punct)| (item.value, punct.is_some()))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, [(1, true), (2, true), (3, true)]);
+
+        let pairs = ret.into_pairs().collect::<Vec<_>>();
+        assert_eq!(pairs.len(), 3);
+    }
+}
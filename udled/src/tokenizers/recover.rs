@@ -0,0 +1,208 @@
+use core::marker::PhantomData;
+
+use alloc::fmt;
+
+use crate::{Buffer, Error, Reader, Span, Tokenizer, WithSpan, EOF};
+
+/// The token produced by [Recover]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovered<T> {
+    Token(T),
+    /// The tokenizer failed, and the input in [Span] was skipped
+    Error(Span),
+}
+
+impl<T> Recovered<T> {
+    pub fn ok(self) -> Option<T> {
+        match self {
+            Recovered::Token(token) => Some(token),
+            Recovered::Error(_) => None,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, Recovered::Error(_))
+    }
+}
+
+impl<T: WithSpan> WithSpan for Recovered<T> {
+    fn span(&self) -> Span {
+        match self {
+            Recovered::Token(token) => token.span(),
+            Recovered::Error(span) => *span,
+        }
+    }
+}
+
+/// Recover from failures of [T].
+///
+/// On failure the error is reported to the reader, and input is skipped until
+/// [S] matches. If [consume] is set, the match of [S] is skipped as well.
+/// Recovery has to skip something, so it fails with the original error
+/// at the end of input or when [S] matches right away without being consumed
+pub struct Recover<T, S, B> {
+    tokenizer: T,
+    sync: S,
+    consume: bool,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, S, B> Recover<T, S, B> {
+    /// Skip until [sync], leaving it in the input
+    pub const fn until(tokenizer: T, sync: S) -> Recover<T, S, B> {
+        Recover {
+            tokenizer,
+            sync,
+            consume: false,
+            buffer: PhantomData,
+        }
+    }
+
+    /// Skip until and including [sync]
+    pub const fn with(tokenizer: T, sync: S) -> Recover<T, S, B> {
+        Recover {
+            tokenizer,
+            sync,
+            consume: true,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, S: Clone, B> Clone for Recover<T, S, B> {
+    fn clone(&self) -> Self {
+        Recover {
+            tokenizer: self.tokenizer.clone(),
+            sync: self.sync.clone(),
+            consume: self.consume,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, S: Copy, B> Copy for Recover<T, S, B> {}
+
+impl<T: fmt::Debug, S: fmt::Debug, B> fmt::Debug for Recover<T, S, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Recover")
+            .field("tokenizer", &self.tokenizer)
+            .field("sync", &self.sync)
+            .field("consume", &self.consume)
+            .finish()
+    }
+}
+
+impl<'input, T, S, B> Tokenizer<'input, B> for Recover<T, S, B>
+where
+    T: Tokenizer<'input, B>,
+    S: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = Recovered<T::Token>;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        let start = reader.position();
+
+        let error = match reader.parse(&self.tokenizer) {
+            Ok(token) => return Ok(Recovered::Token(token)),
+            // Committed failures and exceeded limits are not recovered from
+            Err(err) if err.is_committed() => return Err(err),
+            Err(err) => err,
+        };
+
+        while !reader.is_silent(&self.sync) && !reader.is(EOF) {
+            reader.read()?;
+        }
+
+        if self.consume && reader.is_silent(&self.sync) {
+            reader.eat(&self.sync)?;
+        }
+
+        let end = reader.position();
+        if end == start {
+            return Err(error);
+        }
+
+        reader.report(error);
+
+        Ok(Recovered::Error(Span::new(start, end)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec::Vec};
+
+    use crate::{
+        tokenizers::{Alphabetic, Digit},
+        ErrorKind, Input, Limit, TokenizerExt,
+    };
+
+    use super::*;
+
+    #[test]
+    fn many() {
+        let mut input = Input::new("a;1;b;");
+
+        let list = input
+            .parse((Alphabetic, ';').recover_with(';').many())
+            .unwrap();
+
+        assert_eq!(list.value.len(), 3);
+        assert_eq!(list.value[1], Recovered::Error(Span::new(2, 4)));
+        assert!(!list.value[2].is_error());
+
+        let diagnostics = input.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].position(), 2);
+    }
+
+    #[test]
+    fn punctuated() {
+        let mut input = Input::new("[1,x,3]");
+
+        let list = input
+            .parse((
+                '[',
+                Digit(10).recover_until(','.or(']')).punctuated(','),
+                ']',
+            ))
+            .unwrap();
+
+        let items = list.1.items().collect::<Vec<_>>();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1], &Recovered::Error(Span::new(3, 4)));
+
+        assert_eq!(input.diagnostics().len(), 1);
+        assert_eq!(
+            input.diagnostics()[0].to_string(),
+            "@3: expected digit, found `x`"
+        );
+    }
+
+    #[test]
+    fn backtracked() {
+        let mut input = Input::new("1;");
+
+        // The recovery happens inside a branch which fails, so it is not reported
+        let ret = input.parse(((Alphabetic.recover_with(';'), 'x').or(Digit(10)), ';'));
+
+        assert!(ret.is_ok());
+        assert!(input.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn committed() {
+        let mut input = Input::new("ax;");
+
+        let err = input.parse(('a', 'b'.cut()).recover_with(';')).unwrap_err();
+        assert!(err.is_committed());
+        assert_eq!(err.position(), 1);
+        assert!(input.diagnostics().is_empty());
+
+        let mut input = Input::new("a;").with_max_depth(1);
+        let err = input.parse(Alphabetic.recover_with(';')).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Limit(Limit::Depth(1))));
+        assert!(input.diagnostics().is_empty());
+    }
+}
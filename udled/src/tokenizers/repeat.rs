@@ -0,0 +1,210 @@
+use core::{
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
+
+use alloc::{borrow::Cow, fmt, format, vec, vec::Vec};
+
+use crate::{Buffer, Error, Item, Reader, Span, Tokenizer};
+
+/// Matches [T] between [min] and [max] times.
+///
+/// Fails with "expected at least 3 items, found 2" if [T] matches too few
+/// times, with the failure of [T] attached. Set what the items are called
+/// with [with_name](RepeatRange::with_name)
+pub struct RepeatRange<T, B> {
+    tokenizer: T,
+    min: usize,
+    max: Option<usize>,
+    name: Cow<'static, str>,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, B> RepeatRange<T, B> {
+    /// # Panics
+    ///
+    /// If [range] is empty, like `5..5` or `..0`
+    pub fn new<R: RangeBounds<usize>>(tokenizer: T, range: R) -> RepeatRange<T, B> {
+        let min = match range.start_bound() {
+            Bound::Included(min) => *min,
+            Bound::Excluded(min) => min.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+
+        let max = match range.end_bound() {
+            Bound::Included(max) => Some(*max),
+            Bound::Excluded(max) => Some(max.checked_sub(1).expect("empty repetition range")),
+            Bound::Unbounded => None,
+        };
+
+        assert!(max.map_or(true, |max| min <= max), "empty repetition range");
+
+        RepeatRange {
+            tokenizer,
+            min,
+            max,
+            name: Cow::Borrowed("items"),
+            buffer: PhantomData,
+        }
+    }
+
+    /// Name of the items used in errors. Defaults to "items"
+    pub fn with_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    fn repeat<'input, F>(
+        &self,
+        reader: &mut Reader<'_, 'input, B>,
+        mut next: F,
+    ) -> Result<(), Error>
+    where
+        B: Buffer<'input>,
+        F: FnMut(&mut Reader<'_, 'input, B>) -> Result<(), Error>,
+    {
+        let mut count = 0;
+
+        while self.max.map_or(true, |max| count < max) {
+            let position = reader.position();
+
+            match next(reader) {
+                Ok(()) => count += 1,
+                Err(err) if err.is_committed() => return Err(err),
+                Err(err) if count < self.min => {
+                    return Err(too_few(err, count, self.min, self.max, &self.name))
+                }
+                Err(_) => break,
+            }
+
+            // Without progress, it would match forever
+            if reader.position() == position && count >= self.min {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// "expected at least [min] [name], found [count]", with [error] attached
+pub(crate) fn too_few(
+    error: Error,
+    count: usize,
+    min: usize,
+    max: Option<usize>,
+    name: &str,
+) -> Error {
+    let message = if Some(min) == max {
+        format!("expected {min} {name}, found {count}")
+    } else {
+        format!("expected at least {min} {name}, found {count}")
+    };
+
+    Error::new_with(error.position(), message, vec![error])
+}
+
+impl<T: Clone, B> Clone for RepeatRange<T, B> {
+    fn clone(&self) -> Self {
+        RepeatRange {
+            tokenizer: self.tokenizer.clone(),
+            min: self.min,
+            max: self.max,
+            name: self.name.clone(),
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, B> fmt::Debug for RepeatRange<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RepeatRange")
+            .field("tokenizer", &self.tokenizer)
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .finish()
+    }
+}
+
+impl<'input, T, B> Tokenizer<'input, B> for RepeatRange<T, B>
+where
+    B: Buffer<'input>,
+    T: Tokenizer<'input, B>,
+{
+    type Token = Item<Vec<T::Token>>;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        let start = reader.position();
+        let mut output = Vec::new();
+
+        self.repeat(reader, |reader| {
+            output.push(reader.parse(&self.tokenizer)?);
+            Ok(())
+        })?;
+
+        let end = reader.position();
+
+        Ok(Item::new(Span::new(start, end), output))
+    }
+
+    fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
+        self.repeat(reader, |reader| reader.eat(&self.tokenizer))
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        self.min == 0 || self.tokenizer.peek(reader)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use crate::{tokenizers::Digit, Input, TokenizerExt};
+
+    #[test]
+    fn bounds() {
+        let mut input = Input::new("12345");
+
+        let ret = input.parse(Digit(10).repeat_range(2..=3)).unwrap();
+        assert_eq!(ret.value.len(), 3);
+
+        let ret = input.parse(Digit(10).at_most(5)).unwrap();
+        assert_eq!(ret.value.len(), 2);
+
+        let ret = input.parse(Digit(10).many0()).unwrap();
+        assert!(ret.value.is_empty());
+        assert_eq!(ret.span.start, 5);
+    }
+
+    #[test]
+    fn too_few() {
+        let mut input = Input::new("12a");
+
+        let err = input
+            .parse(Digit(10).at_least(3).with_name("digits"))
+            .unwrap_err();
+        assert_eq!(err.position(), 2);
+        assert!(err
+            .to_string()
+            .starts_with("@2: expected at least 3 digits, found 2"));
+
+        let err = input.parse(Digit(10).repeat(4)).unwrap_err();
+        assert!(err.to_string().starts_with("@2: expected 4 items, found 2"));
+
+        let ret = input.parse(Digit(10).repeat(0)).unwrap();
+        assert!(ret.value.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "empty repetition range")]
+    fn empty_range() {
+        let _ = Input::new("1").parse(Digit(10).repeat_range(5..5));
+    }
+
+    #[test]
+    #[should_panic(expected = "empty repetition range")]
+    fn empty_range_to_zero() {
+        let _ = Input::new("1").parse(Digit(10).repeat_range(..0));
+    }
+}
@@ -0,0 +1,192 @@
+use core::marker::PhantomData;
+
+use alloc::fmt;
+
+use crate::{Buffer, Error, Reader, Tokenizer};
+
+/// Trivia skipped before tokens
+pub(crate) trait Skip<'input, B: Buffer<'input>> {
+    fn skip(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error>;
+}
+
+impl<'input, B, T> Skip<'input, B> for T
+where
+    B: Buffer<'input>,
+    T: Tokenizer<'input, B>,
+{
+    fn skip(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
+        loop {
+            let start = reader.position();
+            if !reader.is_silent(self) {
+                return Ok(());
+            }
+
+            reader.eat(self)?;
+
+            if reader.position() == start {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Skip trivia matched by [S], like whitespace and comments, before each
+/// token of [T] and after it.
+///
+/// The skipper applies to every token read inside [T], so tokens made of
+/// several parts, like numbers built with `Digit(10).many()`, have to be
+/// wrapped in [NoSkip] to not allow trivia between their parts
+pub struct Skipping<T, S, B> {
+    tokenizer: T,
+    skip: S,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, S, B> Skipping<T, S, B> {
+    pub const fn new(tokenizer: T, skip: S) -> Skipping<T, S, B> {
+        Skipping {
+            tokenizer,
+            skip,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, S: Clone, B> Clone for Skipping<T, S, B> {
+    fn clone(&self) -> Self {
+        Skipping {
+            tokenizer: self.tokenizer.clone(),
+            skip: self.skip.clone(),
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, S: Copy, B> Copy for Skipping<T, S, B> {}
+
+impl<T: fmt::Debug, S: fmt::Debug, B> fmt::Debug for Skipping<T, S, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Skipping")
+            .field("tokenizer", &self.tokenizer)
+            .field("skip", &self.skip)
+            .finish()
+    }
+}
+
+impl<'input, T, S, B> Tokenizer<'input, B> for Skipping<T, S, B>
+where
+    T: Tokenizer<'input, B>,
+    S: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = T::Token;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        reader.with_skipper(Some(&self.skip), |reader| {
+            let token = reader.parse(&self.tokenizer)?;
+            reader.skip()?;
+            Ok(token)
+        })
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        reader
+            .with_skipper(Some(&self.skip), |reader| Ok(reader.is(&self.tokenizer)))
+            .unwrap_or(false)
+    }
+}
+
+/// Read [T] without skipping trivia between its parts.
+/// Trivia before the lexeme is still skipped
+pub struct NoSkip<T, B> {
+    tokenizer: T,
+    buffer: PhantomData<fn(B)>,
+}
+
+impl<T, B> NoSkip<T, B> {
+    pub const fn new(tokenizer: T) -> NoSkip<T, B> {
+        NoSkip {
+            tokenizer,
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, B> Clone for NoSkip<T, B> {
+    fn clone(&self) -> Self {
+        NoSkip {
+            tokenizer: self.tokenizer.clone(),
+            buffer: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy, B> Copy for NoSkip<T, B> {}
+
+impl<T: fmt::Debug, B> fmt::Debug for NoSkip<T, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NoSkip")
+            .field("tokenizer", &self.tokenizer)
+            .finish()
+    }
+}
+
+impl<'input, T, B> Tokenizer<'input, B> for NoSkip<T, B>
+where
+    T: Tokenizer<'input, B>,
+    B: Buffer<'input>,
+{
+    type Token = T::Token;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        reader.no_skip(|reader| reader.parse(&self.tokenizer))
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        reader
+            .no_skip(|reader| Ok(reader.is(&self.tokenizer)))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec::Vec};
+
+    use crate::{
+        tokenizers::{Digit, WhiteSpace},
+        Input, TokenizerExt,
+    };
+
+    #[test]
+    fn skipping() {
+        let mut input = Input::new(" [ 1 ,2,  3 ] ");
+
+        let number = Digit(10).many().slice().no_skip();
+        let list = input
+            .parse(('[', number.punctuated(','), ']').skipping(WhiteSpace))
+            .unwrap();
+
+        let items = list.1.items().map(|m| m.value).collect::<Vec<_>>();
+        assert_eq!(items, ["1", "2", "3"]);
+
+        // The trailing trivia is skipped too
+        assert!(input.parse(crate::EOF).is_ok());
+    }
+
+    #[test]
+    fn no_skip() {
+        let mut input = Input::new("[1 2]");
+
+        let err = input
+            .parse(('[', Digit(10).many().no_skip(), ']').skipping(WhiteSpace))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "@3: expected `]`, found `2`");
+
+        let mut input = Input::new("[1 2]");
+        let ret = input
+            .parse(('[', Digit(10).many(), ']').skipping(WhiteSpace))
+            .unwrap();
+        assert_eq!(ret.1.value.len(), 2);
+    }
+}
@@ -0,0 +1,152 @@
+use core::fmt;
+
+use crate::{Buffer, Error, Expected, Item, Reader, Text, Tokenizer};
+
+/// Match an item equal to [T].
+/// Used with token buffers to match a token by its kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kind<T>(pub T);
+
+impl<'input, T, B> Tokenizer<'input, B> for Kind<T>
+where
+    T: PartialEq + fmt::Debug,
+    B: Buffer<'input, Item = T>,
+{
+    type Token = Item<T>;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        if !self.peek(reader) {
+            return Err(
+                reader.expected(Expected::Named(Text::format(format_args!("{:?}", self.0))))
+            );
+        }
+        reader.read_item()
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        reader.peek_ch().as_ref() == Some(&self.0)
+    }
+}
+
+pub const fn satisfy<F>(name: &'static str, predicate: F) -> Satisfy<F> {
+    Satisfy::new(name, predicate)
+}
+
+/// Match an item for which [F] returns true.
+/// [name] describes the matched items in errors
+#[derive(Clone, Copy)]
+pub struct Satisfy<F> {
+    name: &'static str,
+    predicate: F,
+}
+
+impl<F> Satisfy<F> {
+    pub const fn new(name: &'static str, predicate: F) -> Satisfy<F> {
+        Satisfy { name, predicate }
+    }
+}
+
+impl<F> fmt::Debug for Satisfy<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Satisfy").field("name", &self.name).finish()
+    }
+}
+
+impl<'input, F, B> Tokenizer<'input, B> for Satisfy<F>
+where
+    F: Fn(&B::Item) -> bool,
+    B: Buffer<'input>,
+{
+    type Token = Item<B::Item>;
+
+    fn to_token(&self, reader: &mut Reader<'_, 'input, B>) -> Result<Self::Token, Error> {
+        if !self.peek(reader) {
+            return Err(reader.expected(Expected::Class(self.name)));
+        }
+        reader.read_item()
+    }
+
+    fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
+        reader.peek_ch().is_some_and(|item| (self.predicate)(&item))
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(self.name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    use crate::{tokenizers::Puntuated, Input, Span, TokenizerExt};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Token {
+        Ident(&'static str),
+        Comma,
+        Semi,
+    }
+
+    fn tokens() -> Vec<Item<Token>> {
+        // "a, b;"
+        vec![
+            Item::new(Span::new(0, 1), Token::Ident("a")),
+            Item::new(Span::new(1, 2), Token::Comma),
+            Item::new(Span::new(3, 4), Token::Ident("b")),
+            Item::new(Span::new(4, 5), Token::Semi),
+        ]
+    }
+
+    #[test]
+    fn parse_tokens() {
+        let tokens = tokens();
+        let mut input = Input::new(&tokens);
+
+        let ident = satisfy("identifier", |token: &Token| {
+            matches!(token, Token::Ident(_))
+        });
+
+        let (list, semi) = input
+            .parse((Puntuated::new(ident, Kind(Token::Comma)), Kind(Token::Semi)))
+            .unwrap();
+
+        let idents = list.items().map(|m| m.value.clone()).collect::<Vec<_>>();
+        assert_eq!(idents, [Token::Ident("a"), Token::Ident("b")]);
+        assert_eq!(semi.span, Span::new(4, 5));
+    }
+
+    #[test]
+    fn error_position() {
+        let tokens = tokens();
+        let mut input = Input::new(&tokens);
+
+        let err = input
+            .parse((Kind(Token::Ident("a")), Kind(Token::Semi)))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "@1: expected Semi, found Comma");
+
+        let err = input
+            .parse((Kind(Token::Ident("a")), Kind(Token::Ident("b"))))
+            .unwrap_err();
+        assert_eq!(err.span(), Span::new(1, 2));
+    }
+
+    #[test]
+    fn failing_kind_does_not_allocate() {
+        let tokens = tokens();
+        let mut input = Input::new(&tokens);
+
+        let first = Kind(Token::Semi).or(Kind(Token::Ident("a")));
+        input.parse(&first).unwrap();
+
+        let count = crate::test_alloc::allocations(|| {
+            input.reset();
+            input.parse(&first).unwrap();
+        });
+
+        assert_eq!(count, 0);
+    }
+}
@@ -8,6 +8,8 @@ pub const fn until<T, U, B>(tokenizer: T, until: U) -> Until<T, U, B> {
     Until::new(tokenizer, until)
 }
 
+/// Matches [T] until [U] matches, without consuming [U].
+/// Fails if the input ends before [U], or if [T] fails before it
 pub struct Until<T, U, B> {
     tokenizer: T,
     until: U,
@@ -22,6 +24,30 @@ impl<T, U, B> Until<T, U, B> {
             buffer: PhantomData,
         }
     }
+
+    /// Run [next] for each item until [U] matches.
+    /// [U] is only peeked, as it is left in the input
+    fn each<'input, F>(&self, reader: &mut Reader<'_, 'input, B>, mut next: F) -> Result<(), Error>
+    where
+        B: Buffer<'input>,
+        U: Tokenizer<'input, B>,
+        F: FnMut(&mut Reader<'_, 'input, B>) -> Result<(), Error>,
+    {
+        loop {
+            if reader.is(EOF) {
+                return Err(match reader.eat(&self.until) {
+                    Err(err) => err,
+                    Ok(_) => reader.error("unexpected eof"),
+                });
+            }
+
+            if reader.is(&self.until) {
+                return Ok(());
+            }
+
+            next(reader)?;
+        }
+    }
 }
 
 impl<T: Clone, U: Clone, B> Clone for Until<T, U, B> {
@@ -57,18 +83,10 @@ where
         let start = reader.position();
         let mut output = Vec::new();
 
-        loop {
-            if reader.is(EOF) {
-                return Err(reader.error("unexepted eof"));
-            }
-
-            if reader.is(&self.until) {
-                break;
-            }
-
-            let next = reader.parse(&self.tokenizer)?;
-            output.push(next);
-        }
+        self.each(reader, |reader| {
+            output.push(reader.parse(&self.tokenizer)?);
+            Ok(())
+        })?;
 
         let end = reader.position();
 
@@ -76,22 +94,36 @@ where
     }
 
     fn eat(&self, reader: &mut Reader<'_, 'input, B>) -> Result<(), Error> {
-        reader.eat(&self.tokenizer)?;
-
-        loop {
-            if reader.is(&self.until) {
-                break;
-            }
-
-            if reader.eat(&self.tokenizer).is_err() {
-                break;
-            }
-        }
-
-        Ok(())
+        self.each(reader, |reader| reader.eat(&self.tokenizer))
     }
 
     fn peek(&self, reader: &mut Reader<'_, 'input, B>) -> bool {
         self.tokenizer.peek(reader)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{tokenizers::Digit, Input, TokenizerExt};
+
+    #[test]
+    fn parse_and_eat() {
+        let digits = Digit(10).until(';');
+
+        for source in ["12;", ";"] {
+            let mut input = Input::new(source);
+            let ret = input.parse(digits).unwrap();
+            assert_eq!(ret.value.len(), source.len() - 1);
+            assert_eq!(input.position(), source.len() - 1);
+
+            let mut input = Input::new(source);
+            input.eat(digits).unwrap();
+            assert_eq!(input.position(), source.len() - 1);
+        }
+
+        for source in ["12", "1x;"] {
+            assert!(Input::new(source).parse(digits).is_err());
+            assert!(Input::new(source).eat(digits).is_err());
+        }
+    }
+}
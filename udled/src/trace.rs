@@ -0,0 +1,177 @@
+use core::{cell::RefCell, fmt};
+
+use alloc::{borrow::Cow, rc::Rc, string::String, vec::Vec};
+
+/// How a reader ran a tokenizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceKind {
+    Parse,
+    Eat,
+    Peek,
+}
+
+impl fmt::Display for TraceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceKind::Parse => f.write_str("parse"),
+            TraceKind::Eat => f.write_str("eat"),
+            TraceKind::Peek => f.write_str("peek"),
+        }
+    }
+}
+
+/// The result of running a tokenizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The tokenizer matched, and the input continues at [end]
+    Matched {
+        end: usize,
+    },
+    Failed,
+}
+
+/// Receives an event when a reader starts and finishes running a tokenizer.
+/// Set it with [Input::with_tracer](crate::Input::with_tracer)
+pub trait Trace {
+    fn enter(&mut self, kind: TraceKind, name: &str, position: usize);
+
+    fn exit(&mut self, kind: TraceKind, name: &str, outcome: Outcome);
+}
+
+#[derive(Debug, Default)]
+struct Tree {
+    lines: Vec<String>,
+    /// Lines of the tokenizers currently running
+    open: Vec<usize>,
+}
+
+/// Records a trace as an indented tree, one line per tokenizer:
+///
+/// ```text
+/// parse value @0 -> @7
+///   peek char @0 -> failed
+/// ```
+///
+/// Clones share the recording, so keep a clone to print it after parsing
+#[derive(Debug, Clone, Default)]
+pub struct TraceTree {
+    tree: Rc<RefCell<Tree>>,
+}
+
+impl TraceTree {
+    pub fn new() -> TraceTree {
+        TraceTree::default()
+    }
+
+    pub fn clear(&self) {
+        let mut tree = self.tree.borrow_mut();
+        tree.lines.clear();
+        tree.open.clear();
+    }
+}
+
+impl Trace for TraceTree {
+    fn enter(&mut self, kind: TraceKind, name: &str, position: usize) {
+        let mut tree = self.tree.borrow_mut();
+
+        let mut line = String::new();
+        for _ in 0..tree.open.len() {
+            line.push_str("  ");
+        }
+        line.push_str(&alloc::format!("{kind} {name} @{position}"));
+
+        let idx = tree.lines.len();
+        tree.lines.push(line);
+        tree.open.push(idx);
+    }
+
+    fn exit(&mut self, _kind: TraceKind, _name: &str, outcome: Outcome) {
+        let mut tree = self.tree.borrow_mut();
+
+        let Some(idx) = tree.open.pop() else {
+            return;
+        };
+
+        let line = &mut tree.lines[idx];
+        match outcome {
+            Outcome::Matched { end } => line.push_str(&alloc::format!(" -> @{end}")),
+            Outcome::Failed => line.push_str(" -> failed"),
+        }
+    }
+}
+
+impl fmt::Display for TraceTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.tree.borrow().lines {
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Shorten a type name by removing module paths, references and lifetimes
+pub(crate) fn type_name<T: ?Sized>() -> Cow<'static, str> {
+    let name = core::any::type_name::<T>().trim_start_matches('&');
+    if !name.contains("::") && !name.contains('\'') {
+        return Cow::Borrowed(name);
+    }
+
+    let mut output = String::with_capacity(name.len());
+    let mut path = String::new();
+
+    for char in name.chars() {
+        if char.is_alphanumeric() || char == '_' || char == ':' {
+            path.push(char);
+            continue;
+        }
+
+        output.push_str(path.rsplit("::").next().unwrap_or_default());
+        path.clear();
+        output.push(char);
+    }
+
+    output.push_str(path.rsplit("::").next().unwrap_or_default());
+
+    Cow::Owned(output.replace("<'_>", "").replace("'_, ", ""))
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use crate::{tokenizers::Digit, Input, TokenizerExt};
+
+    use super::*;
+
+    #[test]
+    fn type_names() {
+        assert_eq!(type_name::<&char>(), "char");
+        assert_eq!(
+            type_name::<crate::tokenizers::Many<Digit, crate::buffer::StringBuffer>>(),
+            "Many<Digit, StringBuffer>"
+        );
+    }
+
+    #[test]
+    fn trace_tree() {
+        let tree = TraceTree::new();
+        let mut input = Input::new("1a").with_tracer(tree.clone());
+
+        input
+            .parse(('a'.or(Digit(10).named("digit")), 'a').named("pair"))
+            .unwrap();
+
+        assert_eq!(
+            tree.to_string(),
+            "\
+parse pair @0 -> @2
+  parse Or<char, Named<Digit, StringBuffer>, StringBuffer> @0 -> @1
+    parse char @0 -> failed
+    parse digit @0 -> @1
+      parse Char @0 -> @1
+  parse char @1 -> @2
+    parse Char @1 -> @2
+"
+        );
+    }
+}
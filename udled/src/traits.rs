@@ -4,11 +4,11 @@ mod sealed {
 
     pub trait Sealed {}
 
-    impl<'a> Sealed for &'a str {}
+    impl Sealed for &str {}
 
     impl Sealed for char {}
 
-    impl<'a> Sealed for &'a [u8] {}
+    impl Sealed for &[u8] {}
 }
 
 pub trait StringExt: sealed::Sealed {
@@ -26,7 +26,7 @@ pub trait StringExt: sealed::Sealed {
     fn is_alphabetic(&self) -> bool;
 }
 
-impl<'a> StringExt for &'a str {
+impl StringExt for &str {
     fn is_ascii_alphanumeric(&self) -> bool {
         self.chars().all(|m| m.is_ascii_alphanumeric())
     }
@@ -112,14 +112,14 @@ pub trait LineBreaks: sealed::Sealed {
     fn count_linebreak(&self) -> usize;
 }
 
-impl<'a> LineBreaks for &'a str {
+impl LineBreaks for &str {
     fn count_linebreak(&self) -> usize {
         self.chars()
             .fold(0, |p, c| p + if c.is_linebreak() { 1 } else { 0 })
     }
 }
 
-impl<'a> LineBreaks for &'a [u8] {
+impl LineBreaks for &[u8] {
     fn count_linebreak(&self) -> usize {
         self.iter().fold(0, |p, c| {
             p + if (*c as char).is_linebreak() { 1 } else { 0 }